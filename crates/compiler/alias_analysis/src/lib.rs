@@ -6,9 +6,10 @@ use morphic_lib::TypeContext;
 use morphic_lib::{
     BlockExpr, BlockId, CalleeSpecVar, ConstDefBuilder, ConstName, EntryPointName, ExprContext,
     FuncDef, FuncDefBuilder, FuncName, ModDefBuilder, ModName, ProgramBuilder, Result,
-    TypeDefBuilder, TypeId, TypeName, UpdateModeVar, ValueId,
+    TypeDefBuilder, TypeId, TypeName, UpdateMode, UpdateModeVar, ValueId,
 };
 use roc_collections::all::{MutMap, MutSet};
+use roc_collections::VecSet;
 use roc_error_macros::internal_error;
 use roc_module::low_level::LowLevel;
 use roc_module::symbol::Symbol;
@@ -16,6 +17,7 @@ use roc_module::symbol::Symbol;
 use roc_mono::ir::{
     Call, CallType, EntryPoint, ErasedField, Expr, HigherOrderLowLevel, HostExposedLambdaSet,
     ListLiteralElement, Literal, ModifyRc, OptLevel, Proc, ProcLayout, SingleEntryPoint, Stmt,
+    UpdateModeId,
 };
 use roc_mono::layout::{
     Builtin, InLayout, Layout, LayoutInterner, LayoutRepr, Niche, RawFunctionLayout,
@@ -30,7 +32,12 @@ pub const STATIC_LIST_NAME: ConstName = ConstName(b"THIS IS A STATIC LIST");
 
 const ENTRY_POINT_NAME: &[u8] = b"mainForHost";
 
-pub fn func_name_bytes(proc: &Proc) -> [u8; SIZE] {
+/// Whether code generators may put list literals made up only of constants in read-only memory.
+/// Such lists must never be updated in place, so they are then modeled as one shared static list;
+/// otherwise every list literal is a fresh allocation.
+pub const STATIC_LIST_LITERALS: bool = false;
+
+pub fn func_name_bytes(proc: &Proc) -> FuncSpecName {
     let bytes = func_name_bytes_help(
         proc.name.name(),
         proc.args.iter().map(|x| x.0),
@@ -40,6 +47,41 @@ pub fn func_name_bytes(proc: &Proc) -> [u8; SIZE] {
     bytes
 }
 
+fn host_exposed_func_name(hels: &HostExposedLambdaSet) -> FuncSpecName {
+    match hels.raw_function_layout {
+        RawFunctionLayout::Function(..) | RawFunctionLayout::ErasedFunction(..) => {
+            let it = hels.proc_layout.arguments.iter().copied();
+            func_name_bytes_help(hels.symbol, it, Niche::NONE, hels.proc_layout.result)
+        }
+        RawFunctionLayout::ZeroArgumentThunk(_) => {
+            func_name_bytes_help(hels.symbol, [], Niche::NONE, hels.proc_layout.result)
+        }
+    }
+}
+
+/// The names alias analysis gives to each specialization of each symbol, so that its solutions
+/// can be correlated with the procs they were computed for.
+pub fn func_names_for<'a: 'r, 'r, I1, I2>(procs: I1, hels: I2) -> MutMap<Symbol, Vec<FuncSpecName>>
+where
+    I1: Iterator<Item = &'r Proc<'a>>,
+    I2: Iterator<Item = &'r HostExposedLambdaSet<'a>>,
+{
+    let mut names: MutMap<Symbol, Vec<FuncSpecName>> = MutMap::default();
+
+    let procs = procs.map(|proc| (proc.name.name(), func_name_bytes(proc)));
+    let hels = hels.map(|hels| (hels.symbol, host_exposed_func_name(hels)));
+
+    for (symbol, name) in procs.chain(hels) {
+        let specializations = names.entry(symbol).or_default();
+
+        if !specializations.contains(&name) {
+            specializations.push(name);
+        }
+    }
+
+    names
+}
+
 #[inline(always)]
 fn debug() -> bool {
     use roc_debug_flags::dbg_do;
@@ -55,6 +97,16 @@ fn debug() -> bool {
 
 const SIZE: usize = 16;
 
+/// The name under which a specialization of a function is known to alias analysis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FuncSpecName([u8; SIZE]);
+
+impl FuncSpecName {
+    pub fn as_func_name(&self) -> FuncName<'_> {
+        FuncName(&self.0)
+    }
+}
+
 #[derive(Debug, Clone, Copy, Hash)]
 struct TagUnionId(u64);
 
@@ -80,7 +132,7 @@ pub fn func_name_bytes_help<'a, I>(
     argument_layouts: I,
     niche: Niche<'a>,
     return_layout: InLayout<'a>,
-) -> [u8; SIZE]
+) -> FuncSpecName
 where
     I: IntoIterator<Item = InLayout<'a>>,
 {
@@ -122,7 +174,7 @@ where
         }
     }
 
-    name_bytes
+    FuncSpecName(name_bytes)
 }
 
 fn bytes_as_ascii(bytes: &[u8]) -> String {
@@ -137,6 +189,16 @@ fn bytes_as_ascii(bytes: &[u8]) -> String {
     buf
 }
 
+/// The result of [spec_program_with_report].
+pub struct ProgramSpecs {
+    pub solutions: morphic_lib::Solutions,
+    /// How many specializations were solved for each function, summed over its layouts.
+    pub spec_counts: MutMap<Symbol, usize>,
+    /// For each update in each function, whether it is done in place in every specialization.
+    /// Editors can use this to point out updates that never allocate.
+    pub in_place_updates: MutMap<(Symbol, UpdateModeId), bool>,
+}
+
 pub fn spec_program<'a, 'r, I1, I2>(
     arena: &'a Bump,
     interner: &'r STLayoutInterner<'a>,
@@ -149,6 +211,44 @@ where
     I1: Iterator<Item = &'r Proc<'a>>,
     I2: Iterator<Item = &'r HostExposedLambdaSet<'a>>,
 {
+    let specs = spec_program_help(arena, interner, opt_level, entry_point, procs, hels, false)?;
+
+    Ok(specs.solutions)
+}
+
+/// Like [spec_program], but also reports how the program was specialized. Building the report
+/// queries the solutions of every function, so only use this when the report is needed.
+pub fn spec_program_with_report<'a, 'r, I1, I2>(
+    arena: &'a Bump,
+    interner: &'r STLayoutInterner<'a>,
+    opt_level: OptLevel,
+    entry_point: roc_mono::ir::EntryPoint<'a>,
+    procs: I1,
+    hels: I2,
+) -> Result<ProgramSpecs>
+where
+    I1: Iterator<Item = &'r Proc<'a>>,
+    I2: Iterator<Item = &'r HostExposedLambdaSet<'a>>,
+{
+    spec_program_help(arena, interner, opt_level, entry_point, procs, hels, true)
+}
+
+fn spec_program_help<'a, 'r, I1, I2>(
+    arena: &'a Bump,
+    interner: &'r STLayoutInterner<'a>,
+    opt_level: OptLevel,
+    entry_point: roc_mono::ir::EntryPoint<'a>,
+    procs: I1,
+    hels: I2,
+    report: bool,
+) -> Result<ProgramSpecs>
+where
+    I1: Iterator<Item = &'r Proc<'a>>,
+    I2: Iterator<Item = &'r HostExposedLambdaSet<'a>>,
+{
+    let mut func_names: MutMap<Symbol, Vec<FuncSpecName>> = MutMap::default();
+    let mut update_sites = Vec::new();
+
     let main_module = {
         let mut m = ModDefBuilder::new();
 
@@ -182,73 +282,68 @@ where
         m.add_const(STATIC_LIST_NAME, static_list_def)?;
 
         let mut type_definitions = MutSet::default();
+        let mut missing_low_levels = VecSet::default();
         let mut host_exposed_functions = Vec::new();
         let mut erased_functions = Vec::new();
 
         for hels in hels {
-            match hels.raw_function_layout {
-                RawFunctionLayout::Function(_, _, _) => {
-                    let it = hels.proc_layout.arguments.iter().copied();
-                    let bytes =
-                        func_name_bytes_help(hels.symbol, it, Niche::NONE, hels.proc_layout.result);
+            let bytes = host_exposed_func_name(hels);
 
-                    host_exposed_functions.push((bytes, hels.proc_layout.arguments));
-                }
-                RawFunctionLayout::ErasedFunction(..) => {
-                    let it = hels.proc_layout.arguments.iter().copied();
-                    let bytes =
-                        func_name_bytes_help(hels.symbol, it, Niche::NONE, hels.proc_layout.result);
-
-                    host_exposed_functions.push((bytes, hels.proc_layout.arguments));
-                }
-                RawFunctionLayout::ZeroArgumentThunk(_) => {
-                    let bytes =
-                        func_name_bytes_help(hels.symbol, [], Niche::NONE, hels.proc_layout.result);
-
-                    host_exposed_functions.push((bytes, hels.proc_layout.arguments));
-                }
-            }
+            host_exposed_functions.push((bytes, hels.proc_layout.arguments));
         }
 
         // all other functions
         for proc in procs {
             let bytes = func_name_bytes(proc);
-            let func_name = FuncName(&bytes);
+            let func_name = bytes.as_func_name();
 
             if debug() {
                 eprintln!(
                     "{:?}: {:?} with {:?} args",
                     proc.name,
-                    bytes_as_ascii(&bytes),
+                    bytes_as_ascii(&bytes.0),
                     (proc.args, proc.ret_layout),
                 );
             }
 
-            let (spec, type_names) = proc_spec(arena, interner, proc)?;
+            let (spec, mut env) = proc_spec(arena, interner, proc, report)?;
+
+            if report {
+                func_names.entry(proc.name.name()).or_default().push(bytes);
+            }
+
+            if let Some(update_modes) = env.update_modes.take() {
+                update_sites.push((proc.name.name(), bytes, update_modes));
+            }
 
             if proc.is_erased {
                 let args = &*arena.alloc_slice_fill_iter(proc.args.iter().map(|(lay, _)| *lay));
                 erased_functions.push((bytes, args));
             }
 
-            type_definitions.extend(type_names);
+            type_definitions.extend(env.type_names);
+            missing_low_levels.insert_all(env.missing_low_levels.into_iter());
 
             m.add_func(func_name, spec)?;
         }
 
+        if debug() && !missing_low_levels.is_empty() {
+            let missing: Vec<_> = missing_low_levels.iter().collect();
+            eprintln!("low-levels modeled pessimistically: {missing:?}");
+        }
+
         match entry_point {
             EntryPoint::Single(SingleEntryPoint {
                 symbol: entry_point_symbol,
                 layout: entry_point_layout,
             }) => {
                 // the entry point wrapper
-                let roc_main_bytes = func_name_bytes_help(
+                let roc_main = func_name_bytes_help(
                     entry_point_symbol,
                     entry_point_layout.arguments.iter().copied(),
                     Niche::NONE,
                     entry_point_layout.result,
                 );
-                let roc_main = FuncName(&roc_main_bytes);
 
                 let mut env = Env::new();
 
@@ -348,10 +443,55 @@ where
         eprintln!("{}", program.to_source_string());
     }
 
-    match opt_level {
-        OptLevel::Development | OptLevel::Normal => morphic_lib::solve_trivial(program),
-        OptLevel::Optimize | OptLevel::Size => morphic_lib::solve(program),
+    let solutions = match opt_level {
+        OptLevel::Development | OptLevel::Normal => morphic_lib::solve_trivial(program)?,
+        OptLevel::Optimize | OptLevel::Size => morphic_lib::solve(program)?,
+    };
+
+    let mod_solutions = solutions.mod_solutions(MOD_APP)?;
+    let mut spec_counts = MutMap::default();
+
+    for (symbol, names) in func_names {
+        let mut count = 0;
+
+        for name in names {
+            count += mod_solutions
+                .func_solutions(name.as_func_name())?
+                .specs()
+                .count();
+        }
+
+        spec_counts.insert(symbol, count);
+    }
+
+    let mut in_place_updates = MutMap::default();
+
+    for (symbol, name, update_modes) in update_sites {
+        let func_solutions = mod_solutions.func_solutions(name.as_func_name())?;
+
+        for update_mode in update_modes {
+            let bytes = update_mode.to_bytes();
+            let update_mode_var = UpdateModeVar(&bytes);
+
+            for spec in func_solutions.specs() {
+                // update modes of low-levels that do not update anything are unknown to morphic
+                let Ok(mode) = func_solutions.spec(spec)?.update_mode(update_mode_var) else {
+                    continue;
+                };
+
+                let in_place = in_place_updates
+                    .entry((symbol, update_mode))
+                    .or_insert(true);
+                *in_place &= mode == UpdateMode::InPlace;
+            }
+        }
     }
+
+    Ok(ProgramSpecs {
+        solutions,
+        spec_counts,
+        in_place_updates,
+    })
 }
 
 /// if you want an "escape hatch" which allows you construct "best-case scenario" values
@@ -374,9 +514,9 @@ fn build_entry_point<'a>(
     env: &mut Env<'a>,
     interner: &STLayoutInterner<'a>,
     layout: roc_mono::ir::ProcLayout<'a>,
-    entry_point_function: Option<FuncName>,
-    host_exposed_functions: &[([u8; SIZE], &'a [InLayout<'a>])],
-    erased_functions: &[([u8; SIZE], &'a [InLayout<'a>])],
+    entry_point_function: Option<FuncSpecName>,
+    host_exposed_functions: &[(FuncSpecName, &'a [InLayout<'a>])],
+    erased_functions: &[(FuncSpecName, &'a [InLayout<'a>])],
 ) -> Result<FuncDef> {
     let mut builder = FuncDefBuilder::new();
     let outer_block = builder.add_block();
@@ -397,7 +537,13 @@ fn build_entry_point<'a>(
 
         let name_bytes = [0; 16];
         let spec_var = CalleeSpecVar(&name_bytes);
-        let result = builder.add_call(block, spec_var, MOD_APP, entry_point_function, argument)?;
+        let result = builder.add_call(
+            block,
+            spec_var,
+            MOD_APP,
+            entry_point_function.as_func_name(),
+            argument,
+        )?;
 
         // to the modelling language, the result disappears into the void
         let unit_type = builder.add_tuple_type(&[])?;
@@ -407,13 +553,17 @@ fn build_entry_point<'a>(
     }
 
     // add fake calls to host-exposed functions so they are specialized
-    for (name_bytes, layouts) in host_exposed_functions.iter().chain(erased_functions) {
-        let host_exposed_func_name = FuncName(name_bytes);
+    let mut called = MutSet::default();
+    called.extend(entry_point_function);
 
-        if Some(host_exposed_func_name) == entry_point_function {
+    for (name_bytes, layouts) in host_exposed_functions.iter().chain(erased_functions) {
+        // the same function may be exposed more than once, but one call specializes it
+        if !called.insert(*name_bytes) {
             continue;
         }
 
+        let host_exposed_func_name = name_bytes.as_func_name();
+
         let block = builder.add_block();
 
         let struct_layout = LayoutRepr::struct_(layouts);
@@ -421,7 +571,7 @@ fn build_entry_point<'a>(
 
         let argument = builder.add_unknown_with(block, &[], type_id)?;
 
-        let spec_var = CalleeSpecVar(name_bytes);
+        let spec_var = CalleeSpecVar(&name_bytes.0);
         let result =
             builder.add_call(block, spec_var, MOD_APP, host_exposed_func_name, argument)?;
 
@@ -448,10 +598,15 @@ fn proc_spec<'a>(
     arena: &'a Bump,
     interner: &STLayoutInterner<'a>,
     proc: &Proc<'a>,
-) -> Result<(FuncDef, MutSet<UnionLayout<'a>>)> {
+    report: bool,
+) -> Result<(FuncDef, Env<'a>)> {
     let mut builder = FuncDefBuilder::new();
     let mut env = Env::new();
 
+    if report {
+        env.update_modes = Some(Vec::new());
+    }
+
     let block = builder.add_block();
 
     // introduce the arguments
@@ -459,6 +614,7 @@ fn proc_spec<'a>(
     for (i, (layout, symbol)) in proc.args.iter().enumerate() {
         let value_id = builder.add_get_tuple_field(block, builder.get_argument(), i as u32)?;
         env.symbols.insert(*symbol, value_id);
+        env.layouts.insert(*symbol, *layout);
 
         argument_layouts.push(*layout);
     }
@@ -484,28 +640,49 @@ fn proc_spec<'a>(
 
     let spec = builder.build(arg_type_id, ret_type_id, root)?;
 
-    Ok((spec, env.type_names))
+    Ok((spec, env))
 }
 
 struct Env<'a> {
     symbols: MutMap<Symbol, ValueId>,
+    /// The layouts of the symbols in `symbols`.
+    layouts: MutMap<Symbol, InLayout<'a>>,
     join_points: MutMap<roc_mono::ir::JoinPointId, morphic_lib::ContinuationId>,
+    /// Join points that are jumped to once and just return one of their parameters, by the
+    /// position of that parameter. Jumps to these are modeled as that argument itself.
+    returning_join_points: MutMap<roc_mono::ir::JoinPointId, usize>,
     type_names: MutSet<UnionLayout<'a>>,
+    /// Low-levels that were modeled pessimistically, for lack of a more precise model.
+    missing_low_levels: VecSet<LowLevel>,
+    /// The update modes of the updates in this function, some of which may go unused. Only
+    /// collected when a report is asked for.
+    update_modes: Option<Vec<UpdateModeId>>,
 }
 
 impl<'a> Env<'a> {
     fn new() -> Self {
         Self {
             symbols: Default::default(),
+            layouts: Default::default(),
             join_points: Default::default(),
+            returning_join_points: Default::default(),
             type_names: Default::default(),
+            missing_low_levels: Default::default(),
+            update_modes: None,
+        }
+    }
+
+    fn record_update_mode(&mut self, update_mode: UpdateModeId) {
+        if let Some(update_modes) = &mut self.update_modes {
+            update_modes.push(update_mode);
         }
     }
 }
 
-fn apply_refcount_operation(
+fn apply_refcount_operation<'a>(
     builder: &mut FuncDefBuilder,
-    env: &mut Env<'_>,
+    interner: &STLayoutInterner<'a>,
+    env: &mut Env<'a>,
     block: BlockId,
     modify_rc: &ModifyRc,
 ) -> Result<()> {
@@ -523,9 +700,14 @@ fn apply_refcount_operation(
             builder.add_recursive_touch(block, argument)?;
         }
         ModifyRc::DecRef(symbol) => {
-            // this is almost certainly suboptimal, but not incorrect
+            // only the outer allocation is freed; the children are left alone
             let argument = env.symbols[symbol];
-            builder.add_recursive_touch(block, argument)?;
+            let layout = interner.get_repr(env.layouts[symbol]);
+
+            match outer_heap_cell(builder, interner, block, argument, layout)? {
+                Some(cell) => builder.add_touch(block, cell)?,
+                None => builder.add_recursive_touch(block, argument)?,
+            };
         }
         ModifyRc::Free(symbol) => {
             // this is almost certainly suboptimal, but not incorrect
@@ -551,6 +733,7 @@ fn stmt_spec<'a>(
         Let(symbol, expr, expr_layout, mut continuation) => {
             let value_id = expr_spec(builder, interner, env, block, *expr_layout, expr)?;
             env.symbols.insert(*symbol, value_id);
+            env.layouts.insert(*symbol, *expr_layout);
 
             let mut queue = vec![symbol];
 
@@ -560,6 +743,7 @@ fn stmt_spec<'a>(
                         let value_id =
                             expr_spec(builder, interner, env, block, *expr_layout, expr)?;
                         env.symbols.insert(*symbol, value_id);
+                        env.layouts.insert(*symbol, *expr_layout);
 
                         queue.push(symbol);
                         continuation = c;
@@ -568,7 +752,7 @@ fn stmt_spec<'a>(
                         // in practice it is common to see a chain of `Let`s interspersed with
                         // Inc/Dec. For e.g. the False interpreter, this caused stack overflows.
                         // so we handle RC operations here to limit recursion depth
-                        apply_refcount_operation(builder, env, block, modify_rc)?;
+                        apply_refcount_operation(builder, interner, env, block, modify_rc)?;
 
                         continuation = c;
                     }
@@ -580,6 +764,7 @@ fn stmt_spec<'a>(
 
             for symbol in queue {
                 env.symbols.remove(symbol);
+                env.layouts.remove(symbol);
             }
 
             Ok(result)
@@ -607,14 +792,46 @@ fn stmt_spec<'a>(
             builder.add_choice(block, &cases)
         }
         Dbg { remainder, .. } => stmt_spec(builder, interner, env, block, layout, remainder),
-        Expect { remainder, .. } => stmt_spec(builder, interner, env, block, layout, remainder),
-        ExpectFx { remainder, .. } => stmt_spec(builder, interner, env, block, layout, remainder),
+        Expect {
+            lookups, remainder, ..
+        }
+        | ExpectFx {
+            lookups, remainder, ..
+        } => {
+            // a failing expectation reads the values it looked up, to report them
+            for symbol in lookups.iter() {
+                builder.add_recursive_touch(block, env.symbols[symbol])?;
+            }
+
+            stmt_spec(builder, interner, env, block, layout, remainder)
+        }
         Ret(symbol) => Ok(env.symbols[symbol]),
         Refcounting(modify_rc, continuation) => {
-            apply_refcount_operation(builder, env, block, modify_rc)?;
+            apply_refcount_operation(builder, interner, env, block, modify_rc)?;
 
             stmt_spec(builder, interner, env, block, layout, continuation)
         }
+        Join {
+            id,
+            parameters,
+            body: Ret(ret_symbol),
+            remainder,
+        } if count_jumps_to(*id, remainder) == 1
+            && parameters.iter().any(|p| p.symbol == *ret_symbol) =>
+        {
+            // The only jump passes its argument straight back out, so model the jump as that
+            // argument rather than going through a continuation.
+            let index = parameters
+                .iter()
+                .position(|p| p.symbol == *ret_symbol)
+                .unwrap();
+
+            env.returning_join_points.insert(*id, index);
+            let value_id = stmt_spec(builder, interner, env, block, layout, remainder)?;
+            env.returning_join_points.remove(id);
+
+            Ok(value_id)
+        }
         Join {
             id,
             parameters,
@@ -659,6 +876,7 @@ fn stmt_spec<'a>(
                         builder.add_get_tuple_field(jp_body_block, jp_argument, i as u32)?;
 
                     env.symbols.insert(p.symbol, value_id);
+                    env.layouts.insert(p.symbol, p.layout);
                 }
 
                 let jp_body_value_id =
@@ -672,6 +890,11 @@ fn stmt_spec<'a>(
 
             builder.add_sub_block(block, BlockExpr(cont_block, cont_value_id))
         }
+        Jump(id, symbols) if env.returning_join_points.contains_key(id) => {
+            let index = env.returning_join_points[id];
+
+            Ok(env.symbols[&symbols[index]])
+        }
         Jump(id, symbols) => {
             let ret_type_id = layout_spec(env, builder, interner, interner.get_repr(layout))?;
             let argument = build_tuple_value(builder, env, block, symbols)?;
@@ -689,6 +912,75 @@ fn stmt_spec<'a>(
     }
 }
 
+/// The heap cell of the outermost allocation of a value of this layout, if it has exactly one.
+fn outer_heap_cell<'a>(
+    builder: &mut FuncDefBuilder,
+    interner: &STLayoutInterner<'a>,
+    block: BlockId,
+    value: ValueId,
+    layout: LayoutRepr<'a>,
+) -> Result<Option<ValueId>> {
+    match layout {
+        LayoutRepr::Builtin(Builtin::Str | Builtin::List(_)) => {
+            let cell = builder.add_get_tuple_field(block, value, LIST_CELL_INDEX)?;
+            Ok(Some(cell))
+        }
+        LayoutRepr::Union(union_layout)
+            if !matches!(union_layout, UnionLayout::NonRecursive(_)) =>
+        {
+            let type_name_bytes = recursive_tag_union_name_bytes(&union_layout).as_bytes();
+            let type_name = TypeName(&type_name_bytes);
+
+            let union_id = builder.add_unwrap_named(block, MOD_APP, type_name, value)?;
+            let cell = builder.add_get_tuple_field(block, union_id, TAG_CELL_INDEX)?;
+            Ok(Some(cell))
+        }
+        LayoutRepr::RecursivePointer(union_layout) => outer_heap_cell(
+            builder,
+            interner,
+            block,
+            value,
+            interner.get_repr(union_layout),
+        ),
+        _ => Ok(None),
+    }
+}
+
+/// How many jumps to the join point `id` there are within `stmt`.
+fn count_jumps_to(id: roc_mono::ir::JoinPointId, stmt: &Stmt) -> usize {
+    use Stmt::*;
+
+    let mut count = 0;
+    let mut stack = vec![stmt];
+
+    while let Some(stmt) = stack.pop() {
+        match stmt {
+            Let(_, _, _, continuation) | Refcounting(_, continuation) => stack.push(continuation),
+            Switch {
+                branches,
+                default_branch,
+                ..
+            } => {
+                stack.extend(branches.iter().map(|(_, _, branch)| branch));
+                stack.push(default_branch.1);
+            }
+            Dbg { remainder, .. } | Expect { remainder, .. } | ExpectFx { remainder, .. } => {
+                stack.push(remainder)
+            }
+            Join {
+                body, remainder, ..
+            } => {
+                stack.push(body);
+                stack.push(remainder);
+            }
+            Jump(target, _) => count += (*target == id) as usize,
+            Ret(_) | Crash(..) => {}
+        }
+    }
+
+    count
+}
+
 fn build_tuple_value(
     builder: &mut FuncDefBuilder,
     env: &Env,
@@ -797,7 +1089,7 @@ fn call_spec<'a>(
             let args_it = arg_layouts.iter().copied();
             let captures_niche = name.niche();
             let bytes = func_name_bytes_help(name.name(), args_it, captures_niche, *ret_layout);
-            let name = FuncName(&bytes);
+            let name = bytes.as_func_name();
             let module = MOD_APP;
             builder.add_call(block, spec_var, module, name, arg_value_id)
         }
@@ -849,6 +1141,7 @@ fn call_spec<'a>(
 
             let mode = update_mode.to_bytes();
             let update_mode_var = UpdateModeVar(&mode);
+            env.record_update_mode(*update_mode);
 
             let args_it = passed_function.argument_layouts.iter().copied();
             let captures_niche = passed_function.name.niche();
@@ -858,7 +1151,7 @@ fn call_spec<'a>(
                 captures_niche,
                 passed_function.return_layout,
             );
-            let name = FuncName(&bytes);
+            let name = bytes.as_func_name();
             let module = MOD_APP;
 
             let closure_env = env.symbols[&passed_function.captured_environment];
@@ -882,8 +1175,15 @@ fn call_spec<'a>(
                 ListMap { xs } => {
                     let list = env.symbols[xs];
 
+                    // When the elements keep their layout, the output is built on top of the
+                    // input, so the first append updates the input's cell. That update is in place
+                    // exactly when the input is unique, and then mapping allocates nothing.
+                    let reuses_input = argument_layouts[0] == *return_layout;
+
                     let loop_body = |builder: &mut FuncDefBuilder, block, state| {
-                        let input_bag = builder.add_get_tuple_field(block, list, LIST_BAG_INDEX)?;
+                        let source = if reuses_input { state } else { list };
+                        let input_bag =
+                            builder.add_get_tuple_field(block, source, LIST_BAG_INDEX)?;
 
                         let element = builder.add_bag_get(block, input_bag)?;
 
@@ -898,7 +1198,11 @@ fn call_spec<'a>(
                     let state_layout = LayoutRepr::Builtin(Builtin::List(*return_layout));
                     let state_type = layout_spec(env, builder, interner, state_layout)?;
 
-                    let init_state = new_list(builder, block, output_element_type)?;
+                    let init_state = if reuses_input {
+                        list
+                    } else {
+                        new_list(builder, block, output_element_type)?
+                    };
 
                     add_loop(builder, block, state_type, init_state, loop_body)
                 }
@@ -1075,7 +1379,7 @@ fn lowlevel_spec<'a>(
     block: BlockId,
     layout: InLayout<'a>,
     op: &LowLevel,
-    update_mode: roc_mono::ir::UpdateModeId,
+    update_mode: UpdateModeId,
     arguments: &[Symbol],
 ) -> Result<ValueId> {
     use LowLevel::*;
@@ -1083,10 +1387,18 @@ fn lowlevel_spec<'a>(
     let type_id = layout_spec(env, builder, interner, interner.get_repr(layout))?;
     let mode = update_mode.to_bytes();
     let update_mode_var = UpdateModeVar(&mode);
+    env.record_update_mode(update_mode);
 
     match op {
-        NumAdd | NumSub => {
-            // NOTE these numeric operations panic (e.g. on overflow)
+        NumAdd | NumSub | NumMul | NumAbs | NumNeg | NumPowInt | NumPow | NumTan | NumAsin
+        | NumAcos | NumSqrtUnchecked | NumLogUnchecked | NumDivTruncUnchecked
+        | NumDivCeilUnchecked | NumRemUnchecked | NumDivFrac | NumRound | NumFloor | NumCeiling => {
+            // NOTE these numeric operations may panic: integer arithmetic on overflow, division
+            // and remainder by zero, rounding to an integer that is out of range, and Dec
+            // operations whose result does not fit in a Dec (these go through f64 and back).
+            //
+            // The numeric operations that read a list (NumBytesToU16 and friends) or build a
+            // string (NumToStr) are not classified here; they are modeled pessimistically.
 
             let pass_block = {
                 let block = builder.add_block();
@@ -1113,6 +1425,33 @@ fn lowlevel_spec<'a>(
             // just dream up a unit value
             builder.add_make_tuple(block, &[])
         }
+        NumAddWrap
+        | NumSubWrap
+        | NumMulWrap
+        | NumAddSaturated
+        | NumSubSaturated
+        | NumMulSaturated
+        | NumSin
+        | NumCos
+        | NumAtan
+        | NumIsNan
+        | NumIsInfinite
+        | NumIsFinite
+        | NumCountLeadingZeroBits
+        | NumCountTrailingZeroBits
+        | NumCountOneBits
+        | NumIntCast
+        | NumToFloatCast
+        | NumIsMultipleOf => {
+            // these numeric operations never panic, and the results of the trigonometric ones
+            // are always in range for a Dec; just dream up a unit value
+            builder.add_make_tuple(block, &[])
+        }
+        NumAddChecked | NumSubChecked | NumMulChecked | NumToIntChecked | NumToFloatChecked => {
+            // these report failure in their result rather than panicking, and the result
+            // holds no heap cells
+            builder.add_unknown_with(block, &[], type_id)
+        }
         Eq | NotEq => {
             // just dream up a unit value
             builder.add_make_tuple(block, &[])
@@ -1121,6 +1460,11 @@ fn lowlevel_spec<'a>(
             // just dream up a unit value
             builder.add_make_tuple(block, &[])
         }
+        NumBitwiseAnd | NumBitwiseOr | NumBitwiseXor | NumShiftLeftBy | NumShiftRightBy
+        | NumShiftRightZfBy => {
+            // just dream up a unit value
+            builder.add_make_tuple(block, &[])
+        }
         ListLen => {
             // TODO should this touch the heap cell?
             // just dream up a unit value
@@ -1179,6 +1523,12 @@ fn lowlevel_spec<'a>(
             let bag = builder.add_get_tuple_field(block, list, LIST_BAG_INDEX)?;
             let cell = builder.add_get_tuple_field(block, list, LIST_CELL_INDEX)?;
 
+            // the two swapped elements are read before the list is updated, like in ListSortWith.
+            // When the indices are equal or out of bounds the swap is a no-op at runtime, so
+            // always modeling the update is merely conservative.
+            let _element_1 = builder.add_bag_get(block, bag)?;
+            let _element_2 = builder.add_bag_get(block, bag)?;
+
             let _unit = builder.add_update(block, update_mode_var, cell)?;
 
             with_new_heap_cell(builder, block, bag)
@@ -1211,6 +1561,18 @@ fn lowlevel_spec<'a>(
 
             list_append(builder, block, update_mode_var, list, to_insert)
         }
+        ListSublist => {
+            // the start and length are numbers, which we model as unit; the result may be a
+            // seamless slice sharing the input's allocation, so it keeps the input's heap cell
+            let list = env.symbols[&arguments[0]];
+
+            let bag = builder.add_get_tuple_field(block, list, LIST_BAG_INDEX)?;
+            let cell = builder.add_get_tuple_field(block, list, LIST_CELL_INDEX)?;
+
+            let _unit = builder.add_touch(block, cell)?;
+
+            builder.add_make_tuple(block, &[cell, bag])
+        }
         StrToUtf8 => {
             let string = env.symbols[&arguments[0]];
 
@@ -1220,6 +1582,40 @@ fn lowlevel_spec<'a>(
 
             builder.add_make_tuple(block, &[cell, bag])
         }
+        StrRepeat => {
+            // the count is a number, which we model as unit; only the string is read
+            let string = env.symbols[&arguments[0]];
+
+            let cell = builder.add_get_tuple_field(block, string, LIST_CELL_INDEX)?;
+            let _unit = builder.add_touch(block, cell)?;
+
+            // the repeated string is a fresh allocation
+            let new_cell = builder.add_new_heap_cell(block)?;
+            builder.add_make_tuple(block, &[new_cell])
+        }
+        StrToNum => {
+            // only the string is read; the result is a number and an error code, with no heap cells
+            let string = env.symbols[&arguments[0]];
+
+            let cell = builder.add_get_tuple_field(block, string, LIST_CELL_INDEX)?;
+            let _unit = builder.add_touch(block, cell)?;
+
+            builder.add_unknown_with(block, &[], type_id)
+        }
+        StrJoinWith => {
+            // every string in the list is read, and so is the separator
+            let list = env.symbols[&arguments[0]];
+            let separator = env.symbols[&arguments[1]];
+
+            let _unit = builder.add_recursive_touch(block, list)?;
+
+            let cell = builder.add_get_tuple_field(block, separator, LIST_CELL_INDEX)?;
+            let _unit = builder.add_touch(block, cell)?;
+
+            // the joined string is a fresh allocation (or the empty string, which has none)
+            let new_cell = builder.add_new_heap_cell(block)?;
+            builder.add_make_tuple(block, &[new_cell])
+        }
         StrFromUtf8Range => {
             let list = env.symbols[&arguments[0]];
 
@@ -1232,8 +1628,9 @@ fn lowlevel_spec<'a>(
 
             builder.add_make_tuple(block, &[byte_index, string, is_ok, problem_code])
         }
-        _other => {
-            // println!("missing {:?}", _other);
+        other => {
+            env.missing_low_levels.insert(*other);
+
             // TODO overly pessimstic
             let arguments: Vec<_> = arguments.iter().map(|symbol| env.symbols[symbol]).collect();
 
@@ -1493,7 +1890,7 @@ fn expr_spec<'a>(
                 bag = builder.add_bag_insert(block, bag, value_id)?;
             }
 
-            if all_constants {
+            if all_constants && STATIC_LIST_LITERALS {
                 new_static_list(builder, block)
             } else {
                 with_new_heap_cell(builder, block, bag)
@@ -1534,6 +1931,7 @@ fn expr_spec<'a>(
 
             let mode = update_mode.to_bytes();
             let update_mode_var = UpdateModeVar(&mode);
+            env.record_update_mode(*update_mode);
 
             let _unit = builder.add_update(block, update_mode_var, heap_cell)?;
 
@@ -1692,6 +2090,46 @@ fn layout_spec_help<'a>(
     }
 }
 
+/// Whether values of this layout are modeled with a heap cell somewhere inside them, i.e. whether
+/// [layout_spec_help] would produce a type containing one. Follows the same cases, without
+/// building any types.
+pub fn layout_has_heap_cell<'a>(interner: &STLayoutInterner<'a>, layout: LayoutRepr<'a>) -> bool {
+    use LayoutRepr::*;
+
+    let any_has_heap_cell = |layouts: &[InLayout<'a>]| {
+        layouts
+            .iter()
+            .any(|layout| layout_has_heap_cell(interner, interner.get_repr(*layout)))
+    };
+
+    match layout {
+        Builtin(builtin) => builtin_has_heap_cell(&builtin),
+        Struct(field_layouts) => any_has_heap_cell(field_layouts),
+        LambdaSet(lambda_set) => layout_has_heap_cell(
+            interner,
+            interner.get_repr(lambda_set.runtime_representation()),
+        ),
+        Union(union_layout) => match union_layout {
+            UnionLayout::NonRecursive(tags) => tags.iter().any(|tag| any_has_heap_cell(tag)),
+            UnionLayout::Recursive(_)
+            | UnionLayout::NullableUnwrapped { .. }
+            | UnionLayout::NullableWrapped { .. }
+            | UnionLayout::NonNullableUnwrapped(_) => true,
+        },
+        Ptr(_) | RecursivePointer(_) | Erased(_) => true,
+        FunctionPointer(_) => false,
+    }
+}
+
+fn builtin_has_heap_cell(builtin: &Builtin) -> bool {
+    use Builtin::*;
+
+    match builtin {
+        Int(_) | Bool | Decimal | Float(_) => false,
+        Str | List(_) => true,
+    }
+}
+
 fn builtin_spec<'a>(
     env: &mut Env<'a>,
     builder: &mut impl TypeContext,
@@ -1840,3 +2278,905 @@ fn erasure_load(
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use roc_module::symbol::{IdentIds, ModuleId};
+    use roc_mono::ir::{
+        BranchInfo, CallSpecId, JoinPointId, LambdaSetId, Param, PassedFunction, SelfRecursive,
+        UpdateModeIds,
+    };
+    use roc_mono::layout::LambdaName;
+    use roc_mono::low_level::HigherOrder;
+    use roc_target::TargetInfo;
+
+    /// Builds the procs of a small program by hand, and solves it.
+    struct Program<'a> {
+        arena: &'a Bump,
+        interner: STLayoutInterner<'a>,
+        ident_ids: IdentIds,
+        update_mode_ids: UpdateModeIds,
+    }
+
+    impl<'a> Program<'a> {
+        fn new(arena: &'a Bump) -> Self {
+            Self {
+                arena,
+                interner: STLayoutInterner::with_capacity(4, TargetInfo::default_x86_64()),
+                ident_ids: IdentIds::default(),
+                update_mode_ids: UpdateModeIds::new(),
+            }
+        }
+
+        fn symbols<const N: usize>(&mut self) -> [Symbol; N] {
+            std::array::from_fn(|_| Symbol::new(ModuleId::ATTR, self.ident_ids.gen_unique()))
+        }
+
+        fn list_of(&mut self, element: InLayout<'a>) -> InLayout<'a> {
+            let repr = LayoutRepr::Builtin(Builtin::List(element));
+            self.interner.insert_direct_no_semantic(repr)
+        }
+
+        fn struct_of(&mut self, fields: &[InLayout<'a>]) -> InLayout<'a> {
+            let repr = LayoutRepr::struct_(self.arena.alloc_slice_copy(fields));
+            self.interner.insert_direct_no_semantic(repr)
+        }
+
+        /// A call to the low-level `op`, and the update mode it was given.
+        fn low_level(&mut self, op: LowLevel, arguments: &[Symbol]) -> (Expr<'a>, UpdateModeId) {
+            let update_mode = self.update_mode_ids.next_id();
+
+            let call = Call {
+                call_type: CallType::LowLevel { op, update_mode },
+                arguments: self.arena.alloc_slice_copy(arguments),
+            };
+
+            (Expr::Call(call), update_mode)
+        }
+
+        /// A call to `proc`. All calls share one specialization id, so a proc may make only one.
+        fn call(&self, proc: &Proc<'a>, arguments: &[Symbol]) -> Expr<'a> {
+            let arg_layouts = self
+                .arena
+                .alloc_slice_fill_iter(proc.args.iter().map(|(layout, _)| *layout));
+
+            Expr::Call(Call {
+                call_type: CallType::ByName {
+                    name: proc.name,
+                    ret_layout: proc.ret_layout,
+                    arg_layouts,
+                    specialization_id: CallSpecId::BACKEND_DUMMY,
+                },
+                arguments: self.arena.alloc_slice_copy(arguments),
+            })
+        }
+
+        fn int(&self, value: i128) -> Expr<'a> {
+            Expr::Literal(Literal::Int(value.to_ne_bytes()))
+        }
+
+        fn tuple(&self, fields: &[Symbol]) -> Expr<'a> {
+            Expr::Struct(self.arena.alloc_slice_copy(fields))
+        }
+
+        /// Binds each symbol in turn, then continues with `end`.
+        fn lets(&self, bindings: Vec<(Symbol, Expr<'a>, InLayout<'a>)>, end: Stmt<'a>) -> Stmt<'a> {
+            let bindings = bindings.into_iter().rev();
+
+            bindings.fold(end, |continuation, (symbol, expr, layout)| {
+                Stmt::Let(symbol, expr, layout, self.arena.alloc(continuation))
+            })
+        }
+
+        fn proc(
+            &self,
+            name: Symbol,
+            args: &[(InLayout<'a>, Symbol)],
+            body: Stmt<'a>,
+            ret_layout: InLayout<'a>,
+        ) -> Proc<'a> {
+            Proc {
+                name: LambdaName::no_niche(name),
+                args: self.arena.alloc_slice_copy(args),
+                body,
+                closure_data_layout: None,
+                ret_layout,
+                is_self_recursive: SelfRecursive::NotSelfRecursive,
+                is_erased: false,
+            }
+        }
+
+        /// Solves the program whose entry point is the first of the `procs`.
+        fn solve(&self, procs: &[Proc<'a>], hels: &[HostExposedLambdaSet<'a>]) -> ProgramSpecs {
+            let main = &procs[0];
+            let arguments = self
+                .arena
+                .alloc_slice_fill_iter(main.args.iter().map(|(layout, _)| *layout));
+
+            let entry_point = EntryPoint::Single(SingleEntryPoint {
+                symbol: main.name.name(),
+                layout: ProcLayout {
+                    arguments,
+                    result: main.ret_layout,
+                    niche: Niche::NONE,
+                },
+            });
+
+            spec_program_with_report(
+                self.arena,
+                &self.interner,
+                OptLevel::Optimize,
+                entry_point,
+                procs.iter(),
+                hels.iter(),
+            )
+            .unwrap()
+        }
+    }
+
+    #[test]
+    fn func_spec_names_compare_by_symbol_and_layouts() {
+        let name =
+            |symbol, argument| func_name_bytes_help(symbol, [argument], Niche::NONE, Layout::I64);
+
+        assert_eq!(
+            name(Symbol::ARG_1, Layout::I64),
+            name(Symbol::ARG_1, Layout::I64)
+        );
+        assert_ne!(
+            name(Symbol::ARG_1, Layout::I64),
+            name(Symbol::ARG_1, Layout::STR)
+        );
+        assert_ne!(
+            name(Symbol::ARG_1, Layout::I64),
+            name(Symbol::ARG_2, Layout::I64)
+        );
+
+        let names = MutSet::from_iter([
+            name(Symbol::ARG_1, Layout::I64),
+            name(Symbol::ARG_1, Layout::I64),
+            name(Symbol::ARG_2, Layout::I64),
+        ]);
+        assert_eq!(names.len(), 2);
+    }
+
+    /// Exposes the thunk `proc` to the host.
+    fn host_exposed_thunk<'a>(proc: &Proc<'a>) -> HostExposedLambdaSet<'a> {
+        HostExposedLambdaSet {
+            id: LambdaSetId(0),
+            symbol: proc.name.name(),
+            proc_layout: ProcLayout {
+                arguments: &[],
+                result: proc.ret_layout,
+                niche: Niche::NONE,
+            },
+            raw_function_layout: RawFunctionLayout::ZeroArgumentThunk(proc.ret_layout),
+        }
+    }
+
+    #[test]
+    fn func_names_for_each_specialization() {
+        let arena = Bump::new();
+        let mut p = Program::new(&arena);
+
+        // g = \x -> x at two types, and h = \{} -> "" exposed to the host
+        let [g, x, h, s] = p.symbols();
+        let g_i64 = p.proc(g, &[(Layout::I64, x)], Stmt::Ret(x), Layout::I64);
+        let g_str = p.proc(g, &[(Layout::STR, x)], Stmt::Ret(x), Layout::STR);
+        let body = p.lets(
+            vec![(s, Expr::Literal(Literal::Str("")), Layout::STR)],
+            Stmt::Ret(s),
+        );
+        let h_proc = p.proc(h, &[], body, Layout::STR);
+
+        let procs = [g_i64, g_str, h_proc];
+        let hels = [host_exposed_thunk(&procs[2])];
+        let names = func_names_for(procs.iter(), hels.iter());
+
+        assert_eq!(names[&g].len(), 2);
+        assert_ne!(names[&g][0], names[&g][1]);
+        assert_eq!(names[&h], vec![func_name_bytes(&procs[2])]);
+    }
+
+    /// The low-levels that `proc` models pessimistically.
+    fn missing_low_levels(p: &Program, proc: &Proc) -> Vec<LowLevel> {
+        let (_, env) = proc_spec(p.arena, &p.interner, proc, false).unwrap();
+
+        env.missing_low_levels.into_iter().collect()
+    }
+
+    #[test]
+    fn missing_low_levels_are_collected_once() {
+        let arena = Bump::new();
+        let mut p = Program::new(&arena);
+
+        // f = \a, b -> Str.concat (Str.concat a b) b
+        let [f, a, b, ab, abb] = p.symbols();
+        let (concat_1, _) = p.low_level(LowLevel::StrConcat, &[a, b]);
+        let (concat_2, _) = p.low_level(LowLevel::StrConcat, &[ab, b]);
+
+        let body = p.lets(
+            vec![(ab, concat_1, Layout::STR), (abb, concat_2, Layout::STR)],
+            Stmt::Ret(abb),
+        );
+        let f_proc = p.proc(f, &[(Layout::STR, a), (Layout::STR, b)], body, Layout::STR);
+
+        assert_eq!(missing_low_levels(&p, &f_proc), [LowLevel::StrConcat]);
+    }
+
+    #[test]
+    fn bitwise_operations_are_modeled() {
+        use LowLevel::*;
+
+        let arena = Bump::new();
+        let mut p = Program::new(&arena);
+
+        // f = \a, b -> each bitwise operation in turn, applied to the previous result and b
+        let [f, a, b] = p.symbols();
+        let ops = [
+            NumBitwiseAnd,
+            NumBitwiseOr,
+            NumBitwiseXor,
+            NumShiftLeftBy,
+            NumShiftRightBy,
+            NumShiftRightZfBy,
+        ];
+
+        let mut bindings = Vec::new();
+        let mut previous = a;
+        for op in ops {
+            let [result] = p.symbols();
+            let (expr, _) = p.low_level(op, &[previous, b]);
+            bindings.push((result, expr, Layout::I64));
+            previous = result;
+        }
+
+        let body = p.lets(bindings, Stmt::Ret(previous));
+        let f_proc = p.proc(f, &[(Layout::I64, a), (Layout::I64, b)], body, Layout::I64);
+
+        assert_eq!(missing_low_levels(&p, &f_proc), []);
+        p.solve(&[f_proc], &[]);
+    }
+
+    #[test]
+    fn numeric_operations_are_modeled_by_whether_they_may_panic() {
+        let arena = Bump::new();
+        let mut p = Program::new(&arena);
+        let checked = p.struct_of(&[Layout::I64, Layout::BOOL]);
+
+        // f = \a, b -> Num.addChecked (Num.intCast (Num.divTrunc a b)) b
+        let [f, a, b, quotient, cast, sum] = p.symbols();
+        let (may_panic, _) = p.low_level(LowLevel::NumDivTruncUnchecked, &[a, b]);
+        let (pure, _) = p.low_level(LowLevel::NumIntCast, &[quotient]);
+        let (reports_failure, _) = p.low_level(LowLevel::NumAddChecked, &[cast, b]);
+
+        let body = p.lets(
+            vec![
+                (quotient, may_panic, Layout::I64),
+                (cast, pure, Layout::I64),
+                (sum, reports_failure, checked),
+            ],
+            Stmt::Ret(sum),
+        );
+        let f_proc = p.proc(f, &[(Layout::I64, a), (Layout::I64, b)], body, checked);
+
+        assert_eq!(missing_low_levels(&p, &f_proc), []);
+        p.solve(&[f_proc], &[]);
+    }
+
+    #[test]
+    fn host_exposed_function_exposed_twice_is_called_once() {
+        let arena = Bump::new();
+        let mut p = Program::new(&arena);
+
+        // main = \x -> x, and h = \{} -> "" exposed to the host under two names
+        let [main, x, h, s] = p.symbols();
+        let main_proc = p.proc(main, &[(Layout::I64, x)], Stmt::Ret(x), Layout::I64);
+        let body = p.lets(
+            vec![(s, Expr::Literal(Literal::Str("")), Layout::STR)],
+            Stmt::Ret(s),
+        );
+        let h_proc = p.proc(h, &[], body, Layout::STR);
+
+        let hels = [host_exposed_thunk(&h_proc), host_exposed_thunk(&h_proc)];
+
+        // a second fake call would reuse the callee spec var of the first, which morphic rejects
+        let specs = p.solve(&[main_proc, h_proc], &hels);
+
+        assert_eq!(specs.spec_counts[&h], 1);
+    }
+
+    #[test]
+    fn constant_list_literals_are_updated_independently() {
+        let arena = Bump::new();
+        let mut p = Program::new(&arena);
+        let list_i64 = p.list_of(Layout::I64);
+        let pair = p.struct_of(&[list_i64, list_i64]);
+
+        // f = \x -> (List.append [1, 2] x, List.append [1, 2] x)
+        let [f, x, list_1, list_2, appended_1, appended_2, result] = p.symbols();
+        let literal = || Expr::Array {
+            elem_layout: Layout::I64,
+            elems: arena.alloc([
+                ListLiteralElement::Literal(Literal::Int(1i128.to_ne_bytes())),
+                ListLiteralElement::Literal(Literal::Int(2i128.to_ne_bytes())),
+            ]),
+        };
+        let (append_1, mode_1) = p.low_level(LowLevel::ListAppendUnsafe, &[list_1, x]);
+        let (append_2, mode_2) = p.low_level(LowLevel::ListAppendUnsafe, &[list_2, x]);
+
+        let body = p.lets(
+            vec![
+                (list_1, literal(), list_i64),
+                (list_2, literal(), list_i64),
+                (appended_1, append_1, list_i64),
+                (appended_2, append_2, list_i64),
+                (result, p.tuple(&[appended_1, appended_2]), pair),
+            ],
+            Stmt::Ret(result),
+        );
+        let f_proc = p.proc(f, &[(Layout::I64, x)], body, pair);
+
+        let specs = p.solve(&[f_proc], &[]);
+
+        // static list literals are one shared list, which is never updated in place
+        assert_eq!(specs.in_place_updates[&(f, mode_1)], !STATIC_LIST_LITERALS);
+        assert_eq!(specs.in_place_updates[&(f, mode_2)], !STATIC_LIST_LITERALS);
+    }
+
+    #[test]
+    fn layouts_with_heap_cells() {
+        let arena = Bump::new();
+        let mut p = Program::new(&arena);
+        let list_i64 = p.list_of(Layout::I64);
+        let numbers = p.struct_of(&[Layout::I64, Layout::U8]);
+        let with_list = p.struct_of(&[Layout::I64, list_i64]);
+
+        let has_heap_cell = |layout| layout_has_heap_cell(&p.interner, p.interner.get_repr(layout));
+
+        assert!(!has_heap_cell(Layout::I64));
+        assert!(has_heap_cell(Layout::STR));
+        assert!(has_heap_cell(list_i64));
+        assert!(!has_heap_cell(numbers));
+        assert!(has_heap_cell(with_list));
+
+        let tags: &[&[InLayout]] = &[&[Layout::I64], &[]];
+        let recursive = LayoutRepr::Union(UnionLayout::Recursive(tags));
+        let non_recursive = LayoutRepr::Union(UnionLayout::NonRecursive(tags));
+
+        assert!(layout_has_heap_cell(&p.interner, recursive));
+        assert!(!layout_has_heap_cell(&p.interner, non_recursive));
+    }
+
+    #[test]
+    fn spec_counts_sum_over_layouts() {
+        let arena = Bump::new();
+        let mut p = Program::new(&arena);
+
+        // g = \y -> y, used at I64 by main = \x -> g x, and at Str by h = \{} -> g ""
+        let [g, y, main, x, r, h, s, t] = p.symbols();
+        let g_i64 = p.proc(g, &[(Layout::I64, y)], Stmt::Ret(y), Layout::I64);
+        let g_str = p.proc(g, &[(Layout::STR, y)], Stmt::Ret(y), Layout::STR);
+
+        let body = p.lets(vec![(r, p.call(&g_i64, &[x]), Layout::I64)], Stmt::Ret(r));
+        let main_proc = p.proc(main, &[(Layout::I64, x)], body, Layout::I64);
+
+        let body = p.lets(
+            vec![
+                (s, Expr::Literal(Literal::Str("")), Layout::STR),
+                (t, p.call(&g_str, &[s]), Layout::STR),
+            ],
+            Stmt::Ret(t),
+        );
+        let h_proc = p.proc(h, &[], body, Layout::STR);
+        let hels = [host_exposed_thunk(&h_proc)];
+
+        let specs = p.solve(&[main_proc, h_proc, g_i64, g_str], &hels);
+
+        assert_eq!(specs.spec_counts[&g], 2);
+        assert_eq!(specs.spec_counts[&main], 1);
+    }
+
+    #[test]
+    fn str_join_with_output_is_a_fresh_string() {
+        let arena = Bump::new();
+        let mut p = Program::new(&arena);
+        let list_str = p.list_of(Layout::STR);
+        let list_u8 = p.list_of(Layout::U8);
+        let triple = p.struct_of(&[list_str, Layout::STR, list_u8]);
+
+        // f = \strings, sep ->
+        //     (strings, sep, Str.toUtf8 (Str.joinWith strings sep) |> List.append 0)
+        let [f, strings, sep, joined, bytes, zero, appended, result] = p.symbols();
+        let (join_with, _) = p.low_level(LowLevel::StrJoinWith, &[strings, sep]);
+        let (to_utf8, _) = p.low_level(LowLevel::StrToUtf8, &[joined]);
+        let (append, append_mode) = p.low_level(LowLevel::ListAppendUnsafe, &[bytes, zero]);
+
+        let body = p.lets(
+            vec![
+                (joined, join_with, Layout::STR),
+                (bytes, to_utf8, list_u8),
+                (zero, p.int(0), Layout::U8),
+                (appended, append, list_u8),
+                (result, p.tuple(&[strings, sep, appended]), triple),
+            ],
+            Stmt::Ret(result),
+        );
+        let f_proc = p.proc(f, &[(list_str, strings), (Layout::STR, sep)], body, triple);
+
+        let specs = p.solve(&[f_proc], &[]);
+
+        // the joined string shares no allocation with the strings or the separator
+        assert!(specs.in_place_updates[&(f, append_mode)]);
+    }
+
+    #[test]
+    fn str_to_num_leaves_its_source_reusable() {
+        let arena = Bump::new();
+        let mut p = Program::new(&arena);
+        let list_u8 = p.list_of(Layout::U8);
+        let parsed = p.struct_of(&[Layout::I64, Layout::BOOL]);
+        let pair = p.struct_of(&[parsed, list_u8]);
+
+        // f = \s -> (Str.toI64 s, Str.toUtf8 s |> List.append 0)
+        let [f, s, number, bytes, zero, appended, result] = p.symbols();
+        let (to_num, _) = p.low_level(LowLevel::StrToNum, &[s]);
+        let (to_utf8, _) = p.low_level(LowLevel::StrToUtf8, &[s]);
+        let (append, append_mode) = p.low_level(LowLevel::ListAppendUnsafe, &[bytes, zero]);
+
+        let body = p.lets(
+            vec![
+                (number, to_num, parsed),
+                (bytes, to_utf8, list_u8),
+                (zero, p.int(0), Layout::U8),
+                (appended, append, list_u8),
+                (result, p.tuple(&[number, appended]), pair),
+            ],
+            Stmt::Ret(result),
+        );
+        let f_proc = p.proc(f, &[(Layout::STR, s)], body, pair);
+
+        assert_eq!(missing_low_levels(&p, &f_proc), []);
+
+        let specs = p.solve(&[f_proc], &[]);
+
+        assert!(specs.in_place_updates[&(f, append_mode)]);
+    }
+
+    #[test]
+    fn unique_list_update_is_reported_in_place() {
+        let arena = Bump::new();
+        let mut p = Program::new(&arena);
+        let list_i64 = p.list_of(Layout::I64);
+
+        // f = \list, x -> List.append list (List.len list + x)
+        let [f, list, x, len, y, appended] = p.symbols();
+        let (length, len_mode) = p.low_level(LowLevel::ListLen, &[list]);
+        let (add, add_mode) = p.low_level(LowLevel::NumAddWrap, &[len, x]);
+        let (append, append_mode) = p.low_level(LowLevel::ListAppendUnsafe, &[list, y]);
+
+        let body = p.lets(
+            vec![
+                (len, length, Layout::I64),
+                (y, add, Layout::I64),
+                (appended, append, list_i64),
+            ],
+            Stmt::Ret(appended),
+        );
+        let f_proc = p.proc(f, &[(list_i64, list), (Layout::I64, x)], body, list_i64);
+
+        let specs = p.solve(&[f_proc], &[]);
+
+        assert!(specs.in_place_updates[&(f, append_mode)]);
+
+        // low-levels that update nothing are left out of the report
+        assert!(!specs.in_place_updates.contains_key(&(f, len_mode)));
+        assert!(!specs.in_place_updates.contains_key(&(f, add_mode)));
+    }
+
+    /// Whether `List.map` appends to its output in place, when the mapped function returns
+    /// `output` elements given `I64` ones, and the input list is or is not used again afterwards.
+    fn list_map_is_in_place(output: InLayout<'static>, list_is_used_after: bool) -> bool {
+        let arena = Bump::new();
+        let mut p = Program::new(&arena);
+        let list_i64 = p.list_of(Layout::I64);
+        let list_output = p.list_of(output);
+        let pair = p.struct_of(&[list_i64, list_output]);
+
+        // f = \list -> List.map list g, or (list, List.map list g), with g = \_ -> 0
+        let [f, g, x, zero, list, captures, mapped, result] = p.symbols();
+        let g_body = p.lets(vec![(zero, p.int(0), output)], Stmt::Ret(zero));
+        let g_proc = p.proc(g, &[(Layout::I64, x)], g_body, output);
+
+        let map_mode = p.update_mode_ids.next_id();
+        let map = Expr::Call(Call {
+            call_type: CallType::HigherOrder(arena.alloc(HigherOrderLowLevel {
+                op: HigherOrder::ListMap { xs: list },
+                closure_env_layout: None,
+                update_mode: map_mode,
+                passed_function: PassedFunction {
+                    name: g_proc.name,
+                    argument_layouts: &[Layout::I64],
+                    return_layout: output,
+                    specialization_id: CallSpecId::BACKEND_DUMMY,
+                    captured_environment: captures,
+                    owns_captured_environment: false,
+                },
+            })),
+            arguments: arena.alloc([list]),
+        });
+
+        let mut bindings = vec![
+            (captures, p.tuple(&[]), Layout::UNIT),
+            (mapped, map, list_output),
+        ];
+        let (ret, ret_layout) = if list_is_used_after {
+            bindings.push((result, p.tuple(&[list, mapped]), pair));
+            (result, pair)
+        } else {
+            (mapped, list_output)
+        };
+
+        let body = p.lets(bindings, Stmt::Ret(ret));
+        let f_proc = p.proc(f, &[(list_i64, list)], body, ret_layout);
+
+        p.solve(&[f_proc, g_proc], &[]).in_place_updates[&(f, map_mode)]
+    }
+
+    #[test]
+    fn list_map_reuses_its_input_when_element_layouts_match() {
+        // the output is built on top of the input, which can only be updated if it is unique
+        assert!(list_map_is_in_place(Layout::I64, false));
+        assert!(!list_map_is_in_place(Layout::I64, true));
+
+        // the output is a fresh list, whatever happens to the input
+        assert!(list_map_is_in_place(Layout::U8, false));
+        assert!(list_map_is_in_place(Layout::U8, true));
+    }
+
+    #[test]
+    fn str_repeat_output_is_a_fresh_string() {
+        let arena = Bump::new();
+        let mut p = Program::new(&arena);
+        let list_u8 = p.list_of(Layout::U8);
+        let pair = p.struct_of(&[Layout::STR, list_u8]);
+
+        // f = \s, n -> (s, Str.toUtf8 (Str.repeat s n) |> List.append 0)
+        let [f, s, n, repeated, bytes, zero, appended, result] = p.symbols();
+        let (repeat, _) = p.low_level(LowLevel::StrRepeat, &[s, n]);
+        let (to_utf8, _) = p.low_level(LowLevel::StrToUtf8, &[repeated]);
+        let (append, append_mode) = p.low_level(LowLevel::ListAppendUnsafe, &[bytes, zero]);
+
+        let body = p.lets(
+            vec![
+                (repeated, repeat, Layout::STR),
+                (bytes, to_utf8, list_u8),
+                (zero, p.int(0), Layout::U8),
+                (appended, append, list_u8),
+                (result, p.tuple(&[s, appended]), pair),
+            ],
+            Stmt::Ret(result),
+        );
+        let f_proc = p.proc(f, &[(Layout::STR, s), (Layout::I64, n)], body, pair);
+
+        let specs = p.solve(&[f_proc], &[]);
+
+        // the bytes share the repeated string's allocation, which is not the input's
+        assert!(specs.in_place_updates[&(f, append_mode)]);
+    }
+
+    /// Whether swapping two elements of a list is done in place, when the list is or is not
+    /// used again afterwards.
+    fn list_swap_is_in_place(list_is_used_after: bool) -> bool {
+        let arena = Bump::new();
+        let mut p = Program::new(&arena);
+        let list_i64 = p.list_of(Layout::I64);
+        let pair = p.struct_of(&[list_i64, list_i64]);
+
+        // f = \list, i, j -> List.swap list i j, or (list, List.swap list i j)
+        let [f, list, i, j, swapped, result] = p.symbols();
+        let (swap, swap_mode) = p.low_level(LowLevel::ListSwap, &[list, i, j]);
+
+        let mut bindings = vec![(swapped, swap, list_i64)];
+        let (ret, ret_layout) = if list_is_used_after {
+            bindings.push((result, p.tuple(&[list, swapped]), pair));
+            (result, pair)
+        } else {
+            (swapped, list_i64)
+        };
+
+        let body = p.lets(bindings, Stmt::Ret(ret));
+        let args = [(list_i64, list), (Layout::U64, i), (Layout::U64, j)];
+        let f_proc = p.proc(f, &args, body, ret_layout);
+
+        p.solve(&[f_proc], &[]).in_place_updates[&(f, swap_mode)]
+    }
+
+    #[test]
+    fn list_swap_is_in_place_unless_the_list_is_used_after() {
+        assert!(list_swap_is_in_place(false));
+        assert!(!list_swap_is_in_place(true));
+    }
+
+    /// Whether appending to a list after taking a sublist of it is done in place, when the
+    /// sublist is or is not used again afterwards.
+    fn list_append_after_sublist_is_in_place(sublist_is_used_after: bool) -> bool {
+        let arena = Bump::new();
+        let mut p = Program::new(&arena);
+        let list_i64 = p.list_of(Layout::I64);
+        let pair = p.struct_of(&[list_i64, list_i64]);
+
+        // f = \list, start, len, x ->
+        //     sub = List.sublist list { start, len }
+        //     (List.append list x, sub or [])
+        let [f, list, start, len, x, sub, empty, appended, result] = p.symbols();
+        let (sublist, _) = p.low_level(LowLevel::ListSublist, &[list, start, len]);
+        let (append, append_mode) = p.low_level(LowLevel::ListAppendUnsafe, &[list, x]);
+        let second = if sublist_is_used_after { sub } else { empty };
+
+        let body = p.lets(
+            vec![
+                (sub, sublist, list_i64),
+                (empty, Expr::EmptyArray, list_i64),
+                (appended, append, list_i64),
+                (result, p.tuple(&[appended, second]), pair),
+            ],
+            Stmt::Ret(result),
+        );
+        let args = [
+            (list_i64, list),
+            (Layout::U64, start),
+            (Layout::U64, len),
+            (Layout::I64, x),
+        ];
+        let f_proc = p.proc(f, &args, body, pair);
+
+        p.solve(&[f_proc], &[]).in_place_updates[&(f, append_mode)]
+    }
+
+    #[test]
+    fn list_sublist_shares_the_allocation_of_its_source() {
+        assert!(list_append_after_sublist_is_in_place(false));
+        assert!(!list_append_after_sublist_is_in_place(true));
+    }
+
+    /// Whether appending to a list is done in place when the result is returned through a join
+    /// point that is jumped to `jumps` times, or directly when `jumps` is zero.
+    fn list_append_before_jump_is_in_place(jumps: usize, list_is_used_after: bool) -> bool {
+        let arena = Bump::new();
+        let mut p = Program::new(&arena);
+        let list_i64 = p.list_of(Layout::I64);
+        let pair = p.struct_of(&[list_i64, list_i64]);
+
+        // f = \list, x ->
+        //     joinpoint j = \r -> r
+        //     result = (List.append list x, list or [])
+        //     jump j result, from one or two branches
+        let [f, list, x, empty, appended, result, j, r] = p.symbols();
+        let (append, append_mode) = p.low_level(LowLevel::ListAppendUnsafe, &[list, x]);
+        let second = if list_is_used_after { list } else { empty };
+
+        let id = JoinPointId(j);
+        let jump = Stmt::Jump(id, arena.alloc([result]));
+        let end = match jumps {
+            0 => Stmt::Ret(result),
+            1 => jump,
+            _ => Stmt::Switch {
+                cond_symbol: x,
+                cond_layout: Layout::I64,
+                branches: arena.alloc([(0, BranchInfo::None, jump.clone())]),
+                default_branch: (BranchInfo::None, arena.alloc(jump)),
+                ret_layout: pair,
+            },
+        };
+
+        let remainder = p.lets(
+            vec![
+                (empty, Expr::EmptyArray, list_i64),
+                (appended, append, list_i64),
+                (result, p.tuple(&[appended, second]), pair),
+            ],
+            end,
+        );
+        let body = match jumps {
+            0 => remainder,
+            _ => Stmt::Join {
+                id,
+                parameters: arena.alloc([Param {
+                    symbol: r,
+                    layout: pair,
+                }]),
+                body: arena.alloc(Stmt::Ret(r)),
+                remainder: arena.alloc(remainder),
+            },
+        };
+        let f_proc = p.proc(f, &[(list_i64, list), (Layout::I64, x)], body, pair);
+
+        p.solve(&[f_proc], &[]).in_place_updates[&(f, append_mode)]
+    }
+
+    #[test]
+    fn jumps_to_returning_join_points_solve_like_returns() {
+        for jumps in 0..=2 {
+            assert!(list_append_before_jump_is_in_place(jumps, false));
+            assert!(!list_append_before_jump_is_in_place(jumps, true));
+        }
+    }
+
+    /// Whether appending to a list taken out of another list is done in place, when the outer
+    /// list is decremented with `Dec` or `DecRef` afterwards.
+    fn list_append_before_outer_decrement_is_in_place(dec_ref: bool) -> bool {
+        let arena = Bump::new();
+        let mut p = Program::new(&arena);
+        let list_i64 = p.list_of(Layout::I64);
+        let list_list_i64 = p.list_of(list_i64);
+
+        // f = \outer, i, x ->
+        //     inner = List.getUnsafe outer i
+        //     appended = List.append inner x
+        //     dec outer, or decref outer
+        //     appended
+        let [f, outer, i, x, inner, appended] = p.symbols();
+        let (get, _) = p.low_level(LowLevel::ListGetUnsafe, &[outer, i]);
+        let (append, append_mode) = p.low_level(LowLevel::ListAppendUnsafe, &[inner, x]);
+        let decrement = if dec_ref {
+            ModifyRc::DecRef(outer)
+        } else {
+            ModifyRc::Dec(outer)
+        };
+
+        let body = p.lets(
+            vec![(inner, get, list_i64), (appended, append, list_i64)],
+            Stmt::Refcounting(decrement, arena.alloc(Stmt::Ret(appended))),
+        );
+        let args = [(list_list_i64, outer), (Layout::U64, i), (Layout::I64, x)];
+        let f_proc = p.proc(f, &args, body, list_i64);
+
+        p.solve(&[f_proc], &[]).in_place_updates[&(f, append_mode)]
+    }
+
+    #[test]
+    fn dec_ref_leaves_the_elements_alone() {
+        // a Dec may free the elements, so it reads them too
+        assert!(!list_append_before_outer_decrement_is_in_place(false));
+        assert!(list_append_before_outer_decrement_is_in_place(true));
+    }
+
+    /// Whether appending to a list is done in place when an `expect` that looks up the list
+    /// comes before or after the append.
+    fn list_append_around_expect_is_in_place(expect_first: bool) -> bool {
+        let arena = Bump::new();
+        let mut p = Program::new(&arena);
+        let list_i64 = p.list_of(Layout::I64);
+
+        // f = \list, x ->
+        //     expect list is ok, before or after
+        //     appended = List.append list x
+        //     appended
+        let [f, list, x, ok, appended] = p.symbols();
+        let (append, append_mode) = p.low_level(LowLevel::ListAppendUnsafe, &[list, x]);
+        let expect = |remainder| Stmt::Expect {
+            condition: ok,
+            region: Default::default(),
+            lookups: arena.alloc([list]),
+            variables: &[],
+            remainder: arena.alloc(remainder),
+        };
+
+        let ok_binding = (ok, Expr::Literal(Literal::Bool(true)), Layout::BOOL);
+        let body = if expect_first {
+            let remainder = p.lets(vec![(appended, append, list_i64)], Stmt::Ret(appended));
+            p.lets(vec![ok_binding], expect(remainder))
+        } else {
+            let remainder = expect(Stmt::Ret(appended));
+            p.lets(vec![ok_binding, (appended, append, list_i64)], remainder)
+        };
+        let f_proc = p.proc(f, &[(list_i64, list), (Layout::I64, x)], body, list_i64);
+
+        p.solve(&[f_proc], &[]).in_place_updates[&(f, append_mode)]
+    }
+
+    #[test]
+    fn expect_reads_its_lookups_where_it_is() {
+        assert!(list_append_around_expect_is_in_place(true));
+        assert!(!list_append_around_expect_is_in_place(false));
+    }
+
+    /// Whether `List.set` is done in place after an element was gotten from the same list, when
+    /// the list is or is not used again afterwards.
+    fn list_set_after_get_is_in_place(list_is_used_after: bool) -> bool {
+        let arena = Bump::new();
+        let mut p = Program::new(&arena);
+        let list_str = p.list_of(Layout::STR);
+        let replaced_layout = p.struct_of(&[list_str, Layout::STR]);
+        let result_layout = p.struct_of(&[replaced_layout, Layout::STR, list_str]);
+
+        // f = \list, i, j, v ->
+        //     x = List.get list i
+        //     (List.set list j v, x, list or [])
+        let [f, list, i, j, v, x, replaced, empty, result] = p.symbols();
+        let (get, _) = p.low_level(LowLevel::ListGetUnsafe, &[list, i]);
+        let (set, set_mode) = p.low_level(LowLevel::ListReplaceUnsafe, &[list, j, v]);
+        let third = if list_is_used_after { list } else { empty };
+
+        let body = p.lets(
+            vec![
+                (x, get, Layout::STR),
+                (replaced, set, replaced_layout),
+                (empty, Expr::EmptyArray, list_str),
+                (result, p.tuple(&[replaced, x, third]), result_layout),
+            ],
+            Stmt::Ret(result),
+        );
+        let args = [
+            (list_str, list),
+            (Layout::U64, i),
+            (Layout::U64, j),
+            (Layout::STR, v),
+        ];
+        let f_proc = p.proc(f, &args, body, result_layout);
+
+        p.solve(&[f_proc], &[]).in_place_updates[&(f, set_mode)]
+    }
+
+    #[test]
+    fn list_set_after_get_waits_only_for_the_list() {
+        // the gotten element has its own allocation, which the set leaves alone
+        assert!(list_set_after_get_is_in_place(false));
+        assert!(!list_set_after_get_is_in_place(true));
+    }
+
+    /// Whether inserting into a dict is done in place after a value was gotten from it, when the
+    /// dict is or is not used again afterwards. The dict is its backing list of entries, which
+    /// is what `Dict.get` and `Dict.insert` work on.
+    fn dict_insert_after_get_is_in_place(dict_is_used_after: bool) -> bool {
+        let arena = Bump::new();
+        let mut p = Program::new(&arena);
+        let entry = p.struct_of(&[Layout::STR, Layout::STR]);
+        let entries = p.list_of(entry);
+        let result_layout = p.struct_of(&[entries, Layout::STR, entries]);
+
+        // f = \data, i, k2, x ->
+        //     v = (List.getUnsafe data i).1
+        //     (List.append data (k2, x), v, data or [])
+        let [f, data, i, k2, x, found, v, new_entry, inserted, empty, result] = p.symbols();
+        let (get, _) = p.low_level(LowLevel::ListGetUnsafe, &[data, i]);
+        let (insert, insert_mode) = p.low_level(LowLevel::ListAppendUnsafe, &[data, new_entry]);
+        let third = if dict_is_used_after { data } else { empty };
+
+        let value = Expr::StructAtIndex {
+            index: 1,
+            field_layouts: arena.alloc([Layout::STR, Layout::STR]),
+            structure: found,
+        };
+
+        let body = p.lets(
+            vec![
+                (found, get, entry),
+                (v, value, Layout::STR),
+                (new_entry, p.tuple(&[k2, x]), entry),
+                (inserted, insert, entries),
+                (empty, Expr::EmptyArray, entries),
+                (result, p.tuple(&[inserted, v, third]), result_layout),
+            ],
+            Stmt::Ret(result),
+        );
+        let args = [
+            (entries, data),
+            (Layout::U64, i),
+            (Layout::STR, k2),
+            (Layout::STR, x),
+        ];
+        let f_proc = p.proc(f, &args, body, result_layout);
+
+        p.solve(&[f_proc], &[]).in_place_updates[&(f, insert_mode)]
+    }
+
+    #[test]
+    fn dict_insert_after_get_waits_only_for_the_dict() {
+        // the gotten value has its own allocation, which the insert leaves alone
+        assert!(dict_insert_after_get_is_in_place(false));
+        assert!(!dict_insert_after_get_is_in_place(true));
+    }
+}
@@ -92,6 +92,10 @@ flags! {
     /// Prints all type mismatches hit during type unification.
     ROC_PRINT_MISMATCHES
 
+    /// Prints the ranks of both sides of every merge during unification, and the rank chosen for
+    /// the merged variable. Useful for debugging let-generalization.
+    ROC_TRACE_RANKS
+
     /// Prints all type variables entered for fixpoint-fixing.
     ROC_PRINT_FIXPOINT_FIXING
 
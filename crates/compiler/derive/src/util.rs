@@ -95,6 +95,7 @@ impl Env<'_> {
                 vars: _,
                 must_implement_ability: _,
                 lambda_sets_to_specialize,
+                changed: _,
                 extra_metadata: _,
             } => {
                 if !lambda_sets_to_specialize.is_empty() {
@@ -135,6 +136,7 @@ impl Env<'_> {
                 vars: _,
                 must_implement_ability: _,
                 lambda_sets_to_specialize: _lambda_sets_to_specialize,
+                changed: _,
                 extra_metadata: specialization_lsets,
             } => {
                 let specialization_lsets: SpecializationLambdaSets = specialization_lsets
@@ -34,7 +34,7 @@ use inkwell::values::{
 use inkwell::{AddressSpace, IntPredicate};
 use inkwell::{FloatPredicate, OptimizationLevel};
 use morphic_lib::{
-    CalleeSpecVar, FuncName, FuncSpec, FuncSpecSolutions, ModSolutions, UpdateMode, UpdateModeVar,
+    CalleeSpecVar, FuncSpec, FuncSpecSolutions, ModSolutions, UpdateMode, UpdateModeVar,
 };
 use roc_builtins::bitcode::{self, FloatWidth, IntWidth};
 use roc_collections::all::{MutMap, MutSet};
@@ -1113,7 +1113,7 @@ fn promote_to_main_function<'a, 'ctx>(
 ) -> (&'static str, FunctionValue<'ctx>) {
     let it = top_level.arguments.iter().copied();
     let bytes = roc_alias_analysis::func_name_bytes_help(symbol, it, Niche::NONE, top_level.result);
-    let func_name = FuncName(&bytes);
+    let func_name = bytes.as_func_name();
     let func_solutions = mod_solutions.func_solutions(func_name).unwrap();
 
     let mut it = func_solutions.specs();
@@ -1162,7 +1162,7 @@ fn promote_to_wasm_test_wrapper<'a, 'ctx>(
 
     let it = top_level.arguments.iter().copied();
     let bytes = roc_alias_analysis::func_name_bytes_help(symbol, it, Niche::NONE, top_level.result);
-    let func_name = FuncName(&bytes);
+    let func_name = bytes.as_func_name();
     let func_solutions = mod_solutions.func_solutions(func_name).unwrap();
 
     let mut it = func_solutions.specs();
@@ -2887,10 +2887,9 @@ fn list_literal<'a, 'ctx>(
     let list_length = elems.len();
     let list_length_intval = env.ptr_int().const_int(list_length as _, false);
 
-    // TODO re-enable, currently causes morphic segfaults because it tries to update
-    // constants in-place...
-    // if element_type.is_int_type() {
-    if false {
+    // constant lists live in read-only memory, so alias analysis must know not to update them
+    // in-place; see roc_alias_analysis::STATIC_LIST_LITERALS
+    if roc_alias_analysis::STATIC_LIST_LITERALS && element_type.is_int_type() {
         let element_type = element_type.into_int_type();
         let element_width = layout_interner.stack_size(element_layout);
         let size = list_length * element_width as usize;
@@ -5380,7 +5379,7 @@ pub(crate) fn build_proc_headers<'a, 'r, 'ctx>(
     let mut headers = std::vec::Vec::with_capacity(procedures.len());
     for ((symbol, layout), proc) in procedures {
         let name_bytes = roc_alias_analysis::func_name_bytes(&proc);
-        let func_name = FuncName(&name_bytes);
+        let func_name = name_bytes.as_func_name();
 
         let func_solutions = mod_solutions.func_solutions(func_name).unwrap();
 
@@ -5441,7 +5440,7 @@ pub fn build_procedures<'a>(
     for (symbol, top_level) in glue_layouts.getters.iter().copied() {
         let it = top_level.arguments.iter().copied();
         let bytes = roc_alias_analysis::func_name_bytes_help(symbol, it, niche, top_level.result);
-        let func_name = FuncName(&bytes);
+        let func_name = bytes.as_func_name();
         let func_solutions = mod_solutions.func_solutions(func_name).unwrap();
 
         let mut it = func_solutions.specs();
@@ -5559,7 +5558,7 @@ pub fn build_procedures_expose_expects<'a>(
         let it = top_level.arguments.iter().copied();
         let bytes =
             roc_alias_analysis::func_name_bytes_help(symbol, it, captures_niche, top_level.result);
-        let func_name = FuncName(&bytes);
+        let func_name = bytes.as_func_name();
         let func_solutions = mod_solutions.func_solutions(func_name).unwrap();
 
         let mut it = func_solutions.specs();
@@ -5853,7 +5852,7 @@ fn expose_alias_to_host<'a>(
                 Niche::NONE,
                 hels.proc_layout.result,
             );
-            let func_name = FuncName(&bytes);
+            let func_name = bytes.as_func_name();
             let func_solutions = mod_solutions.func_solutions(func_name).unwrap();
 
             let mut it = func_solutions.specs();
@@ -379,6 +379,7 @@ pub fn unify(
             vars: _,
             must_implement_ability: _,
             lambda_sets_to_specialize,
+            changed: _,
             extra_metadata,
         } => {
             let mut pools = Pools::default();
@@ -496,6 +496,30 @@ mod test_reporting {
     "###
     );
 
+    test_report!(
+        value_not_exposed_after_non_ascii,
+        indoc!(
+            r#"
+            Str.concat "héllo" List.isempty
+        "#
+        ),
+        @r###"
+    ── NOT EXPOSED ─────────────────────────────────────────── /code/proj/Main.roc ─
+
+    The List module does not expose `isempty`:
+
+    4│      Str.concat "héllo" List.isempty
+                               ^^^^^^^^^^^^
+
+    Did you mean one of these?
+
+        List.isEmpty
+        List.set
+        List.get
+        List.keepIf
+    "###
+    );
+
     test_report!(
         report_unused_def,
         indoc!(
@@ -11695,7 +11719,7 @@ In roc, functions are always written as a lambda, like{}
     This 2nd argument to `contains` has an unexpected type:
 
     7│      List.contains digits '☃'
-                                 ^^^^^
+                                 ^^^
 
     The argument is a Unicode scalar value of type:
 
@@ -1738,7 +1738,7 @@ impl CallSpecId {
     pub const BACKEND_DUMMY: Self = Self { id: 0 };
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct UpdateModeId {
     id: u32,
 }
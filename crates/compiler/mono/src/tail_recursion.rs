@@ -72,7 +72,7 @@ pub fn apply_trmc<'a, 'i>(
                     arena,
                     id,
                     proc.name,
-                    proc.body.clone(),
+                    &proc.body,
                     args.into_bump_slice(),
                     proc.ret_layout,
                 );
@@ -113,13 +113,21 @@ fn make_tail_recursive<'a>(
     arena: &'a Bump,
     id: JoinPointId,
     needle: LambdaName,
-    stmt: Stmt<'a>,
+    stmt: &Stmt<'a>,
     args: &'a [(InLayout<'a>, Symbol, Symbol)],
     ret_layout: InLayout<'a>,
 ) -> Option<Stmt<'a>> {
-    let allocated = arena.alloc(stmt);
-
-    let new_stmt = insert_jumps(arena, allocated, id, needle, args, ret_layout)?;
+    // borrowing `stmt` means that if no jumps are inserted, nothing is cloned or allocated
+    let mut returning_join_points = Vec::new_in(arena);
+    let new_stmt = insert_jumps(
+        arena,
+        stmt,
+        id,
+        needle,
+        args,
+        ret_layout,
+        &mut returning_join_points,
+    )?;
 
     // if we did not early-return, jumps were inserted, we must now add a join point
 
@@ -149,11 +157,12 @@ fn make_tail_recursive<'a>(
 
 fn insert_jumps<'a>(
     arena: &'a Bump,
-    stmt: &'a Stmt<'a>,
+    stmt: &Stmt<'a>,
     goal_id: JoinPointId,
     needle: LambdaName,
     needle_arguments: &'a [(InLayout<'a>, Symbol, Symbol)],
     needle_result: InLayout<'a>,
+    returning_join_points: &mut Vec<'a, JoinPointId>,
 ) -> Option<&'a Stmt<'a>> {
     use Stmt::*;
 
@@ -188,6 +197,32 @@ fn insert_jumps<'a>(
             Some(arena.alloc(jump))
         }
 
+        Let(
+            symbol,
+            Expr::Call(crate::ir::Call {
+                call_type:
+                    CallType::ByName {
+                        name: fsym,
+                        ret_layout,
+                        arg_layouts,
+                        ..
+                    },
+                arguments,
+            }),
+            _,
+            Stmt::Jump(join_point, [jsym]),
+        ) if symbol == jsym
+            && returning_join_points.contains(join_point)
+            && is_equal_function(*fsym, arg_layouts, *ret_layout) =>
+        {
+            // jumping to a join point that immediately returns its argument is the same as
+            // returning, so this is a tail call too
+
+            let jump = Stmt::Jump(goal_id, arguments);
+
+            Some(arena.alloc(jump))
+        }
+
         Let(symbol, expr, layout, cont) => {
             let opt_cont = insert_jumps(
                 arena,
@@ -196,6 +231,7 @@ fn insert_jumps<'a>(
                 needle,
                 needle_arguments,
                 needle_result,
+                returning_join_points,
             );
 
             if opt_cont.is_some() {
@@ -213,6 +249,12 @@ fn insert_jumps<'a>(
             remainder,
             body: continuation,
         } => {
+            let returns_immediately = returns_its_argument(parameters, continuation);
+
+            if returns_immediately {
+                returning_join_points.push(*id);
+            }
+
             let opt_remainder = insert_jumps(
                 arena,
                 remainder,
@@ -220,7 +262,13 @@ fn insert_jumps<'a>(
                 needle,
                 needle_arguments,
                 needle_result,
+                returning_join_points,
             );
+
+            if returns_immediately {
+                returning_join_points.pop();
+            }
+
             let opt_continuation = insert_jumps(
                 arena,
                 continuation,
@@ -228,6 +276,7 @@ fn insert_jumps<'a>(
                 needle,
                 needle_arguments,
                 needle_result,
+                returning_join_points,
             );
 
             if opt_remainder.is_some() || opt_continuation.is_some() {
@@ -258,50 +307,51 @@ fn insert_jumps<'a>(
                 needle,
                 needle_arguments,
                 needle_result,
+                returning_join_points,
             );
 
-            let mut did_change = false;
-
-            let opt_branches = Vec::from_iter_in(
-                branches.iter().map(|(label, info, branch)| {
-                    match insert_jumps(
-                        arena,
-                        branch,
-                        goal_id,
-                        needle,
-                        needle_arguments,
-                        needle_result,
-                    ) {
-                        None => None,
-                        Some(branch) => {
-                            did_change = true;
-                            Some((*label, info.clone(), branch.clone()))
-                        }
+            // the branches are only copied once one of them changes; each branch is pushed in its
+            // original position, whether it was rewritten or not
+            let mut new_branches: Option<Vec<'a, _>> = None;
+
+            for (index, (label, info, branch)) in branches.iter().enumerate() {
+                let opt_branch = insert_jumps(
+                    arena,
+                    branch,
+                    goal_id,
+                    needle,
+                    needle_arguments,
+                    needle_result,
+                    returning_join_points,
+                );
+
+                match (opt_branch, &mut new_branches) {
+                    (None, None) => {}
+                    (None, Some(new_branches)) => {
+                        new_branches.push((*label, info.clone(), branch.clone()));
                     }
-                }),
-                arena,
-            );
+                    (Some(new_branch), Some(new_branches)) => {
+                        new_branches.push((*label, info.clone(), new_branch.clone()));
+                    }
+                    (Some(new_branch), None) => {
+                        let mut rebuilt = Vec::with_capacity_in(branches.len(), arena);
+                        rebuilt.extend(branches[..index].iter().cloned());
+                        rebuilt.push((*label, info.clone(), new_branch.clone()));
 
-            if opt_default.is_some() || did_change {
+                        new_branches = Some(rebuilt);
+                    }
+                }
+            }
+
+            if opt_default.is_some() || new_branches.is_some() {
                 let default_branch = (
                     default_branch.0.clone(),
                     opt_default.unwrap_or(default_branch.1),
                 );
 
-                let branches = if did_change {
-                    let new = Vec::from_iter_in(
-                        opt_branches.into_iter().zip(branches.iter()).map(
-                            |(opt_branch, branch)| match opt_branch {
-                                None => branch.clone(),
-                                Some(new_branch) => new_branch,
-                            },
-                        ),
-                        arena,
-                    );
-
-                    new.into_bump_slice()
-                } else {
-                    branches
+                let branches = match new_branches {
+                    Some(new_branches) => new_branches.into_bump_slice(),
+                    None => branches,
                 };
 
                 Some(arena.alloc(Switch {
@@ -323,6 +373,7 @@ fn insert_jumps<'a>(
                 needle,
                 needle_arguments,
                 needle_result,
+                returning_join_points,
             ) {
                 Some(cont) => Some(arena.alloc(Refcounting(*modify, cont))),
                 None => None,
@@ -340,6 +391,7 @@ fn insert_jumps<'a>(
             needle,
             needle_arguments,
             needle_result,
+            returning_join_points,
         ) {
             Some(cont) => Some(arena.alloc(Dbg {
                 symbol: *symbol,
@@ -362,6 +414,7 @@ fn insert_jumps<'a>(
             needle,
             needle_arguments,
             needle_result,
+            returning_join_points,
         ) {
             Some(cont) => Some(arena.alloc(Expect {
                 condition: *condition,
@@ -386,6 +439,7 @@ fn insert_jumps<'a>(
             needle,
             needle_arguments,
             needle_result,
+            returning_join_points,
         ) {
             Some(cont) => Some(arena.alloc(ExpectFx {
                 condition: *condition,
@@ -403,6 +457,138 @@ fn insert_jumps<'a>(
     }
 }
 
+/// Whether a join point with these parameters and body just returns its argument, e.g. the join
+/// point that a `when` expression bound to a variable returns through.
+fn returns_its_argument(parameters: &[Param], body: &Stmt) -> bool {
+    matches!((parameters, body), ([param], Stmt::Ret(rsym)) if param.symbol == *rsym)
+}
+
+/// Finds the calls to `needle` in `stmt` that are not in tail position, returning the symbols
+/// their results are bound to. Such calls grow the stack, so a frontend can use this to warn about
+/// functions that look tail-recursive but are not.
+pub fn non_tail_self_calls<'a>(
+    arena: &'a Bump,
+    stmt: &Stmt<'a>,
+    needle: LambdaName<'a>,
+) -> Vec<'a, Symbol> {
+    let mut returning_join_points = Vec::new_in(arena);
+    let mut calls = Vec::new_in(arena);
+
+    non_tail_self_calls_help(stmt, needle, &mut returning_join_points, &mut calls);
+
+    calls
+}
+
+fn non_tail_self_calls_help<'a>(
+    stmt: &Stmt<'a>,
+    needle: LambdaName<'a>,
+    returning_join_points: &mut Vec<'a, JoinPointId>,
+    calls: &mut Vec<'a, Symbol>,
+) {
+    match stmt {
+        Stmt::Let(symbol, expr, _, cont) => {
+            if let Expr::Call(Call {
+                call_type: CallType::ByName { name, .. },
+                ..
+            }) = expr
+            {
+                let is_tail_call = match cont {
+                    Stmt::Ret(rsym) => rsym == symbol,
+                    Stmt::Jump(join_point, [jsym]) => {
+                        jsym == symbol && returning_join_points.contains(join_point)
+                    }
+                    _ => false,
+                };
+
+                if *name == needle && !is_tail_call {
+                    calls.push(*symbol);
+                }
+            }
+
+            non_tail_self_calls_help(cont, needle, returning_join_points, calls)
+        }
+        Stmt::Switch {
+            branches,
+            default_branch,
+            ..
+        } => {
+            let it = branches
+                .iter()
+                .map(|(_, _, stmt)| stmt)
+                .chain([default_branch.1]);
+
+            for next in it {
+                non_tail_self_calls_help(next, needle, returning_join_points, calls);
+            }
+        }
+        Stmt::Refcounting(_, next) => {
+            non_tail_self_calls_help(next, needle, returning_join_points, calls)
+        }
+        Stmt::Expect { remainder, .. }
+        | Stmt::ExpectFx { remainder, .. }
+        | Stmt::Dbg { remainder, .. } => {
+            non_tail_self_calls_help(remainder, needle, returning_join_points, calls)
+        }
+        Stmt::Join {
+            id,
+            parameters,
+            body,
+            remainder,
+        } => {
+            let returns_immediately = returns_its_argument(parameters, body);
+
+            if returns_immediately {
+                returning_join_points.push(*id);
+            }
+
+            non_tail_self_calls_help(remainder, needle, returning_join_points, calls);
+
+            if returns_immediately {
+                returning_join_points.pop();
+            }
+
+            non_tail_self_calls_help(body, needle, returning_join_points, calls);
+        }
+        Stmt::Ret(_) | Stmt::Jump(_, _) | Stmt::Crash(_, _) => { /* terminal */ }
+    }
+}
+
+/// Whether `stmt` calls `needle` by name anywhere. After a successful [make_tail_recursive], a
+/// function body should no longer contain a call to itself.
+pub fn contains_self_call(stmt: &Stmt, needle: Symbol) -> bool {
+    match stmt {
+        Stmt::Let(_, expr, _, cont) => {
+            let is_self_call = matches!(
+                expr,
+                Expr::Call(Call {
+                    call_type: CallType::ByName { name, .. },
+                    ..
+                }) if name.name() == needle
+            );
+
+            is_self_call || contains_self_call(cont, needle)
+        }
+        Stmt::Switch {
+            branches,
+            default_branch,
+            ..
+        } => {
+            branches
+                .iter()
+                .any(|(_, _, branch)| contains_self_call(branch, needle))
+                || contains_self_call(default_branch.1, needle)
+        }
+        Stmt::Refcounting(_, cont) => contains_self_call(cont, needle),
+        Stmt::Expect { remainder, .. }
+        | Stmt::ExpectFx { remainder, .. }
+        | Stmt::Dbg { remainder, .. } => contains_self_call(remainder, needle),
+        Stmt::Join {
+            body, remainder, ..
+        } => contains_self_call(body, needle) || contains_self_call(remainder, needle),
+        Stmt::Ret(_) | Stmt::Jump(_, _) | Stmt::Crash(_, _) => false,
+    }
+}
+
 #[derive(Debug, Default)]
 struct TrmcCandidateSet {
     interner: arrayvec::ArrayVec<Symbol, 64>,
@@ -476,6 +662,19 @@ impl TrmcCandidateSet {
     }
 }
 
+/// Finds the self-recursive calls of `proc` that can be turned into a loop by TRMC. A candidate
+/// call is one whose result is used only as an argument to a constructor that is then returned
+/// immediately, e.g. for `Cons (f x) (map f xs)`:
+///
+/// ```text
+/// let r = CallByName map f xs;
+/// ...                             # statements that do not use `r`
+/// let c = TagId(1) Cons y r;
+/// ret c;
+/// ```
+///
+/// The function must return a recursive tag union. Calls whose result is used in any other way,
+/// or that are not followed by such a constructor on every path, are not candidates.
 fn trmc_candidates<'a, I>(interner: &'_ I, proc: &'_ Proc<'a>) -> TrmcCandidateSet
 where
     I: LayoutInterner<'a>,
@@ -1129,3 +1328,258 @@ fn stmt_contains_symbol_nonrec(stmt: &Stmt, needle: Symbol) -> bool {
         Stmt::Crash(symbol, _) => needle == *symbol,
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ir::{BranchInfo, CallSpecId};
+
+    fn call<'a>(name: LambdaName<'a>, arguments: &'a [Symbol]) -> Expr<'a> {
+        Expr::Call(Call {
+            call_type: CallType::ByName {
+                name,
+                ret_layout: Layout::I64,
+                arg_layouts: &[Layout::I64],
+                specialization_id: CallSpecId::BACKEND_DUMMY,
+            },
+            arguments,
+        })
+    }
+
+    /// `let r = f n; ret r`
+    fn tail_call<'a>(arena: &'a Bump, f: LambdaName<'a>, n: Symbol, r: Symbol) -> Stmt<'a> {
+        Stmt::Let(
+            r,
+            call(f, arena.alloc([n])),
+            Layout::I64,
+            arena.alloc(Stmt::Ret(r)),
+        )
+    }
+
+    /// `let r = f n; let s = n * r; ret s`
+    fn non_tail_call<'a>(
+        arena: &'a Bump,
+        f: LambdaName<'a>,
+        n: Symbol,
+        r: Symbol,
+        s: Symbol,
+    ) -> Stmt<'a> {
+        let mul = Expr::Call(Call {
+            call_type: CallType::LowLevel {
+                op: LowLevel::NumMul,
+                update_mode: UpdateModeId::BACKEND_DUMMY,
+            },
+            arguments: arena.alloc([n, r]),
+        });
+
+        Stmt::Let(
+            r,
+            call(f, arena.alloc([n])),
+            Layout::I64,
+            arena.alloc(Stmt::Let(s, mul, Layout::I64, arena.alloc(Stmt::Ret(s)))),
+        )
+    }
+
+    #[test]
+    fn non_tail_self_call_is_reported() {
+        let arena = Bump::new();
+        let f = LambdaName::no_niche(Symbol::ARG_1);
+        let (n, r, s) = (Symbol::ARG_2, Symbol::ARG_3, Symbol::ARG_4);
+
+        let stmt = non_tail_call(&arena, f, n, r, s);
+
+        assert_eq!(non_tail_self_calls(&arena, &stmt, f).as_slice(), &[r]);
+    }
+
+    #[test]
+    fn tail_self_call_is_not_reported() {
+        let arena = Bump::new();
+        let f = LambdaName::no_niche(Symbol::ARG_1);
+        let (n, r) = (Symbol::ARG_2, Symbol::ARG_3);
+
+        let stmt = tail_call(&arena, f, n, r);
+
+        assert!(non_tail_self_calls(&arena, &stmt, f).is_empty());
+    }
+
+    #[test]
+    fn tail_self_call_through_returning_join_point_is_not_reported() {
+        let arena = Bump::new();
+        let f = LambdaName::no_niche(Symbol::ARG_1);
+        let (n, r, x) = (Symbol::ARG_2, Symbol::ARG_3, Symbol::ARG_4);
+        let j = JoinPointId(Symbol::ARG_5);
+
+        // joinpoint j x = ret x in let r = f n; jump j r
+        let stmt = Stmt::Join {
+            id: j,
+            parameters: arena.alloc([Param {
+                symbol: x,
+                layout: Layout::I64,
+            }]),
+            body: arena.alloc(Stmt::Ret(x)),
+            remainder: arena.alloc(Stmt::Let(
+                r,
+                call(f, arena.alloc([n])),
+                Layout::I64,
+                arena.alloc(Stmt::Jump(j, arena.alloc([r]))),
+            )),
+        };
+
+        assert!(non_tail_self_calls(&arena, &stmt, f).is_empty());
+    }
+
+    #[test]
+    fn calls_to_other_functions_are_not_reported() {
+        let arena = Bump::new();
+        let f = LambdaName::no_niche(Symbol::ARG_1);
+        let g = LambdaName::no_niche(Symbol::ARG_6);
+        let (n, r, s) = (Symbol::ARG_2, Symbol::ARG_3, Symbol::ARG_4);
+
+        let stmt = non_tail_call(&arena, g, n, r, s);
+
+        assert!(non_tail_self_calls(&arena, &stmt, f).is_empty());
+    }
+
+    #[test]
+    fn no_tail_calls_allocates_nothing() {
+        let stmt_arena = Bump::new();
+        let f = LambdaName::no_niche(Symbol::ARG_1);
+        let (n, r, s) = (Symbol::ARG_2, Symbol::ARG_3, Symbol::ARG_4);
+        let stmt = non_tail_call(&stmt_arena, f, n, r, s);
+        let args = stmt_arena.alloc([(Layout::I64, n, Symbol::ARG_5)]);
+
+        let arena = Bump::new();
+        let id = JoinPointId(Symbol::ARG_6);
+        let transformed = make_tail_recursive(&arena, id, f, &stmt, args, Layout::I64);
+
+        assert!(transformed.is_none());
+        assert_eq!(arena.allocated_bytes(), 0);
+    }
+
+    /// `switch n { 0 -> branch0, 1 -> branch1, _ -> default }`
+    fn switch<'a>(
+        arena: &'a Bump,
+        n: Symbol,
+        [branch0, branch1]: [Stmt<'a>; 2],
+        default: Stmt<'a>,
+    ) -> Stmt<'a> {
+        Stmt::Switch {
+            cond_symbol: n,
+            cond_layout: Layout::I64,
+            branches: arena.alloc([
+                (0, BranchInfo::None, branch0),
+                (1, BranchInfo::None, branch1),
+            ]),
+            default_branch: (BranchInfo::None, arena.alloc(default)),
+            ret_layout: Layout::I64,
+        }
+    }
+
+    #[test]
+    fn switch_without_tail_calls_allocates_nothing() {
+        let stmt_arena = Bump::new();
+        let f = LambdaName::no_niche(Symbol::ARG_1);
+        let (n, r, s) = (Symbol::ARG_2, Symbol::ARG_3, Symbol::ARG_4);
+        let stmt = switch(
+            &stmt_arena,
+            n,
+            [
+                non_tail_call(&stmt_arena, f, n, r, s),
+                non_tail_call(&stmt_arena, f, n, r, s),
+            ],
+            non_tail_call(&stmt_arena, f, n, r, s),
+        );
+        let args = stmt_arena.alloc([(Layout::I64, n, Symbol::ARG_5)]);
+
+        let arena = Bump::new();
+        let id = JoinPointId(Symbol::ARG_6);
+        let transformed = make_tail_recursive(&arena, id, f, &stmt, args, Layout::I64);
+
+        assert!(transformed.is_none());
+        assert_eq!(arena.allocated_bytes(), 0);
+    }
+
+    #[test]
+    fn switch_branches_are_rebuilt_from_the_first_change() {
+        let arena = Bump::new();
+        let f = LambdaName::no_niche(Symbol::ARG_1);
+        let (n, r, s) = (Symbol::ARG_2, Symbol::ARG_3, Symbol::ARG_4);
+        let stmt = switch(
+            &arena,
+            n,
+            [
+                non_tail_call(&arena, f, n, r, s),
+                tail_call(&arena, f, n, r),
+            ],
+            non_tail_call(&arena, f, n, r, s),
+        );
+        let args = arena.alloc([(Layout::I64, n, Symbol::ARG_5)]);
+        let id = JoinPointId(Symbol::ARG_6);
+
+        let transformed = make_tail_recursive(&arena, id, f, &stmt, args, Layout::I64);
+
+        match transformed {
+            Some(Stmt::Join {
+                body:
+                    Stmt::Switch {
+                        branches,
+                        default_branch,
+                        ..
+                    },
+                ..
+            }) => {
+                assert!(matches!(
+                    branches,
+                    [(0, _, Stmt::Let(..)), (1, _, Stmt::Jump(jump_id, _))] if *jump_id == id
+                ));
+                assert!(matches!(default_branch.1, Stmt::Let(..)));
+            }
+            other => panic!("expected a join point around a switch, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn tail_call_becomes_jump() {
+        let arena = Bump::new();
+        let f = LambdaName::no_niche(Symbol::ARG_1);
+        let (n, r) = (Symbol::ARG_2, Symbol::ARG_3);
+        let stmt = tail_call(&arena, f, n, r);
+        let args = arena.alloc([(Layout::I64, n, Symbol::ARG_5)]);
+        let id = JoinPointId(Symbol::ARG_6);
+
+        let transformed = make_tail_recursive(&arena, id, f, &stmt, args, Layout::I64);
+
+        assert!(matches!(
+            transformed,
+            Some(Stmt::Join { id: join_id, body: Stmt::Jump(jump_id, [arg]), .. })
+                if join_id == id && *jump_id == id && *arg == n
+        ));
+    }
+
+    #[test]
+    fn no_self_call_after_tail_call_elimination() {
+        let arena = Bump::new();
+        let f = LambdaName::no_niche(Symbol::ARG_1);
+        let (n, r) = (Symbol::ARG_2, Symbol::ARG_3);
+        let stmt = tail_call(&arena, f, n, r);
+        let args = arena.alloc([(Layout::I64, n, Symbol::ARG_5)]);
+        let id = JoinPointId(Symbol::ARG_6);
+
+        assert!(contains_self_call(&stmt, f.name()));
+
+        let transformed = make_tail_recursive(&arena, id, f, &stmt, args, Layout::I64).unwrap();
+
+        assert!(!contains_self_call(&transformed, f.name()));
+    }
+
+    #[test]
+    fn self_call_remains_when_not_in_tail_position() {
+        let arena = Bump::new();
+        let f = LambdaName::no_niche(Symbol::ARG_1);
+        let (n, r, s) = (Symbol::ARG_2, Symbol::ARG_3, Symbol::ARG_4);
+        let stmt = non_tail_call(&arena, f, n, r, s);
+
+        assert!(contains_self_call(&stmt, f.name()));
+        assert!(!contains_self_call(&stmt, Symbol::ARG_7));
+    }
+}
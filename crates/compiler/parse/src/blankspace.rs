@@ -210,6 +210,32 @@ where
     }
 }
 
+/// Consumes a run of spaces, producing how many there were. Newlines and comments are left for
+/// other parsers. If the run starts a line, it becomes that line's indentation.
+pub fn count_spaces<'a, E>() -> impl Parser<'a, u32, E>
+where
+    E: 'a + SpaceProblem,
+{
+    move |_arena, state: State<'a>, _min_indent: u32| {
+        let at_line_start = state.pos() == state.line_start;
+        let count = simple_eat_whitespace(state.bytes());
+
+        let mut state = state.advance(count);
+        if at_line_start {
+            state = state.mark_current_indent();
+        }
+
+        let progress = Progress::from_consumed(count);
+        match state.bytes().first() {
+            Some(b'\t') => Err((
+                progress,
+                E::space_problem(BadInputError::HasTab, state.pos()),
+            )),
+            _ => Ok((progress, count as u32, state)),
+        }
+    }
+}
+
 pub fn simple_eat_whitespace(bytes: &[u8]) -> usize {
     let mut i = 0;
     while i < bytes.len() {
@@ -357,6 +383,153 @@ mod tests {
                 fast_eat_until_control_character(&bytes));
         }
     }
+
+    #[test]
+    fn count_spaces_sets_indentation() {
+        use crate::parser::EExpr;
+
+        let arena = Bump::new();
+        let state = State::new(b"x\n   y").advance(1).advance_newline();
+
+        let (progress, count, state) = count_spaces::<EExpr>().parse(&arena, state, 0).unwrap();
+
+        assert_eq!((progress, count), (MadeProgress, 3));
+        assert_eq!((state.line_indent(), state.column()), (3, 3));
+        assert_eq!(state.bytes(), b"y");
+    }
+
+    #[test]
+    fn count_spaces_stops_at_newline() {
+        use crate::parser::EExpr;
+
+        let arena = Bump::new();
+        let state = State::new(b"x  \n y").advance(1);
+
+        let (_, count, state) = count_spaces::<EExpr>().parse(&arena, state, 0).unwrap();
+
+        assert_eq!(count, 2);
+        assert_eq!(state.line_indent(), 0);
+        assert_eq!(state.bytes(), b"\n y");
+    }
+
+    #[test]
+    fn count_spaces_rejects_tabs() {
+        use crate::parser::EExpr;
+
+        let arena = Bump::new();
+        let result = count_spaces::<EExpr>().parse(&arena, State::new(b"  \ty"), 0);
+
+        assert_eq!(
+            result.map(|(progress, count, _)| (progress, count)),
+            Err((
+                MadeProgress,
+                EExpr::Space(BadInputError::HasTab, Position::new(2))
+            ))
+        );
+    }
+
+    #[test]
+    fn spaces_skip_comments_and_blank_lines() {
+        use crate::parser::EExpr;
+
+        let arena = Bump::new();
+        let state = State::new(b"x  # one\n\n  # two\n   y").advance(1);
+
+        let (progress, spaces, state) = spaces::<EExpr>().parse(&arena, state, 0).unwrap();
+
+        assert_eq!(progress, MadeProgress);
+        assert_eq!(
+            spaces,
+            &[
+                CommentOrNewline::LineComment(" one"),
+                CommentOrNewline::Newline,
+                CommentOrNewline::LineComment(" two"),
+            ]
+        );
+        assert_eq!((state.line_indent(), state.column()), (3, 3));
+        assert_eq!(state.bytes(), b"y");
+    }
+
+    #[test]
+    fn spaces_keep_indentation_without_newline() {
+        use crate::parser::EExpr;
+
+        let arena = Bump::new();
+        let state = State::new(b"x    y").advance(1);
+
+        let (_, spaces, state) = spaces::<EExpr>().parse(&arena, state, 0).unwrap();
+
+        assert!(spaces.is_empty());
+        assert_eq!((state.line_indent(), state.column()), (0, 5));
+    }
+
+    /// A single character, as an item of an indented block.
+    fn block_item<'a>() -> impl Parser<'a, char, crate::parser::EExpr<'a>> {
+        crate::parser::any(crate::parser::EExpr::Start)
+    }
+
+    #[test]
+    fn indented_block_skips_blank_lines_and_comments() {
+        let arena = Bump::new();
+        let state = State::new(b"x\n  a\n\n  # note\n  b\ny").advance(1);
+
+        let (progress, items, state) = indented_block(block_item())
+            .parse(&arena, state, 0)
+            .unwrap();
+
+        assert_eq!(progress, MadeProgress);
+        assert_eq!(items.as_slice(), ['a', 'b']);
+        assert_eq!(state.bytes(), b"\ny");
+    }
+
+    #[test]
+    fn indented_block_ends_at_dedent() {
+        let arena = Bump::new();
+        let state = State::new(b"  x\n    a\n  b")
+            .advance(2)
+            .mark_current_indent()
+            .advance(1);
+
+        let (_, items, state) = indented_block(block_item())
+            .parse(&arena, state, 0)
+            .unwrap();
+
+        assert_eq!(items.as_slice(), ['a']);
+        assert_eq!(state.bytes(), b"\n  b");
+    }
+
+    #[test]
+    fn indented_block_needs_items_on_new_lines() {
+        let arena = Bump::new();
+        let state = State::new(b"x a").advance(1);
+
+        let (progress, items, state) = indented_block(block_item())
+            .parse(&arena, state, 0)
+            .unwrap();
+
+        assert_eq!(progress, NoProgress);
+        assert!(items.is_empty());
+        assert_eq!(state.bytes(), b" a");
+    }
+
+    #[test]
+    fn tab_indentation_is_rejected() {
+        use crate::parser::EExpr;
+
+        let arena = Bump::new();
+        let state = State::new(b"x\n\ty");
+        let state = state.advance(1);
+
+        let result = space0_e(EExpr::IndentStart).parse(&arena, state, 0);
+
+        assert_eq!(
+            result.map(|(progress, spaces, _)| (progress, spaces)),
+            Err((
+                MadeProgress,
+                EExpr::Space(BadInputError::HasTab, Position::new(2))
+            ))
+        );
+    }
 }
 
 pub fn space0_e<'a, E>(
@@ -398,6 +571,53 @@ where
     }
 }
 
+/// Parses the items of an indented block with `parser`: one per line, each line indented more
+/// than the line the block starts on, and all of them by the same amount. Blank lines and
+/// comments between items are skipped. The block ends at the first line indented differently,
+/// or one that `parser` fails on without making progress; the space before it is not consumed.
+pub fn indented_block<'a, P, T, E>(parser: P) -> impl Parser<'a, Vec<'a, T>, E>
+where
+    P: Parser<'a, T, E>,
+    T: 'a,
+    E: 'a + SpaceProblem,
+{
+    move |arena, state: State<'a>, min_indent: u32| {
+        let start = state.pos();
+        let base_indent = state.line_indent();
+        let mut item_indent = None;
+        let mut items = Vec::new_in(arena);
+        let mut state = state;
+
+        loop {
+            let (_, _, item_state) = spaces().parse(arena, state.clone(), min_indent)?;
+
+            let on_new_line = item_state.line_start != state.line_start;
+            let indent = item_state.line_indent();
+
+            if !on_new_line || item_state.has_reached_end() || indent <= base_indent {
+                break;
+            }
+
+            if *item_indent.get_or_insert(indent) != indent {
+                break;
+            }
+
+            match parser.parse(arena, item_state, min_indent) {
+                Ok((_, item, next_state)) => {
+                    items.push(item);
+                    state = next_state;
+                }
+                Err((NoProgress, _)) => break,
+                Err(fail) => return Err(fail),
+            }
+        }
+
+        let progress = Progress::from_consumed((state.pos().offset - start.offset) as usize);
+
+        Ok((progress, items, state))
+    }
+}
+
 pub fn loc_spaces<'a, E>() -> impl Parser<'a, &'a [Loc<CommentOrNewline<'a>>], E>
 where
     E: 'a + SpaceProblem,
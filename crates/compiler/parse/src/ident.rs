@@ -660,3 +660,85 @@ fn chomp_access_chain<'a>(buffer: &'a [u8], parts: &mut Vec<'a, Accessor<'a>>) -
         Ok(chomped as u32)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_with<'a, P>(
+        arena: &'a Bump,
+        parser: P,
+        input: &'a str,
+    ) -> Result<(&'a str, &'a [u8]), Progress>
+    where
+        P: Parser<'a, &'a str, ()>,
+    {
+        parser
+            .parse(arena, State::new(input.as_bytes()), 0)
+            .map(|(_, ident, state)| (ident, state.bytes()))
+            .map_err(|(progress, ())| progress)
+    }
+
+    #[test]
+    fn lowercase_ident_stops_at_non_identifier() {
+        let arena = Bump::new();
+
+        assert_eq!(
+            parse_with(&arena, lowercase_ident(), "fooBar2 = 1"),
+            Ok(("fooBar2", &b" = 1"[..]))
+        );
+        assert_eq!(
+            parse_with(&arena, lowercase_ident(), "whenever"),
+            Ok(("whenever", &b""[..]))
+        );
+    }
+
+    #[test]
+    fn lowercase_ident_rejects_keywords() {
+        let arena = Bump::new();
+
+        for keyword in crate::keyword::KEYWORDS {
+            assert_eq!(
+                parse_with(&arena, lowercase_ident(), keyword),
+                Err(NoProgress)
+            );
+            assert_eq!(
+                parse_with(&arena, lowercase_ident_keyword_e(), keyword),
+                Err(MadeProgress)
+            );
+        }
+    }
+
+    #[test]
+    fn single_letter_ident_at_eof() {
+        let arena = Bump::new();
+
+        assert_eq!(
+            parse_with(&arena, lowercase_ident(), "x"),
+            Ok(("x", &b""[..]))
+        );
+        assert_eq!(
+            parse_with(&arena, uppercase_ident(), "X"),
+            Ok(("X", &b""[..]))
+        );
+        assert_eq!(parse_with(&arena, lowercase_ident(), ""), Err(NoProgress));
+    }
+
+    #[test]
+    fn case_of_leading_letter_selects_parser() {
+        let arena = Bump::new();
+
+        assert_eq!(
+            parse_with(&arena, uppercase_ident(), "Foo.bar"),
+            Ok(("Foo", &b".bar"[..]))
+        );
+        assert_eq!(
+            parse_with(&arena, uppercase_ident(), "foo"),
+            Err(NoProgress)
+        );
+        assert_eq!(
+            parse_with(&arena, lowercase_ident(), "Foo"),
+            Err(NoProgress)
+        );
+    }
+}
@@ -1311,6 +1311,24 @@ where
     }
 }
 
+/// Negative lookahead: succeeds if `p` fails at the current position, and fails if `p` succeeds.
+/// Never consumes any input.
+pub fn not_followed_by<'a, T, E, F, P>(f: F, p: P) -> impl Parser<'a, (), E>
+where
+    T: 'a,
+    E: 'a,
+    F: Fn(Position) -> E,
+    P: Parser<'a, T, E>,
+{
+    move |arena: &'a bumpalo::Bump, state: State<'a>, min_indent: u32| {
+        let original_state = state.clone();
+        match p.parse(arena, state, min_indent) {
+            Ok(_) => Err((NoProgress, f(original_state.pos()))),
+            Err(_) => Ok((NoProgress, (), original_state)),
+        }
+    }
+}
+
 pub fn fail<'a, T, E, F>(f: F) -> impl Parser<'a, T, E>
 where
     T: 'a,
@@ -1488,6 +1506,66 @@ where
     }
 }
 
+/// Runs `parser` only if the current line is indented by more than `indent` columns.
+/// Otherwise fails with `to_error`, without making progress.
+pub fn indented_more_than<'a, P, T, ToError, E>(
+    indent: u32,
+    to_error: ToError,
+    parser: P,
+) -> impl Parser<'a, T, E>
+where
+    P: Parser<'a, T, E>,
+    ToError: Fn(Position) -> E,
+    E: 'a,
+{
+    move |arena, state: State<'a>, min_indent| {
+        if state.line_indent() > indent {
+            parser.parse(arena, state, min_indent)
+        } else {
+            Err((NoProgress, to_error(state.pos())))
+        }
+    }
+}
+
+/// Runs `parser` only if it would start at exactly (zero-based) `column`, e.g. `0` for top-level
+/// definitions. Otherwise fails with `to_error`, without making progress.
+pub fn at_column<'a, P, T, ToError, E>(
+    column: u32,
+    to_error: ToError,
+    parser: P,
+) -> impl Parser<'a, T, E>
+where
+    P: Parser<'a, T, E>,
+    ToError: Fn(Position) -> E,
+    E: 'a,
+{
+    move |arena, state: State<'a>, min_indent| {
+        if state.column() == column {
+            parser.parse(arena, state, min_indent)
+        } else {
+            Err((NoProgress, to_error(state.pos())))
+        }
+    }
+}
+
+/// Runs `parser`, also producing the indentation of the line it started on, so that callers can
+/// scope nested blocks to it.
+///
+/// Parsers take their state by value, so a failing `parser` cannot leave the caller's state
+/// with a changed indentation.
+pub fn with_indent<'a, P, T, E>(parser: P) -> impl Parser<'a, (u32, T), E>
+where
+    P: Parser<'a, T, E>,
+    E: 'a,
+{
+    move |arena, state: State<'a>, min_indent| {
+        let indent = state.line_indent();
+        let (progress, output, state) = parser.parse(arena, state, min_indent)?;
+
+        Ok((progress, (indent, output), state))
+    }
+}
+
 pub fn specialize<'a, F, P, T, X, Y>(map_error: F, parser: P) -> impl Parser<'a, T, Y>
 where
     F: Fn(X, Position) -> Y,
@@ -1536,6 +1614,137 @@ where
     }
 }
 
+/// Runs `parser` on `input`, which it must consume entirely. If any input is left over, fails
+/// with `to_error` at the start of the leftover input.
+pub fn parse_str<'a, P, A, E, ToError>(
+    arena: &'a Bump,
+    parser: P,
+    input: &'a str,
+    to_error: ToError,
+) -> Result<A, E>
+where
+    P: Parser<'a, A, E>,
+    ToError: Fn(Position) -> E,
+    E: 'a,
+{
+    match parser.parse(arena, State::new(input.as_bytes()), 0) {
+        Ok((_, output, state)) if state.has_reached_end() => Ok(output),
+        Ok((_, _, state)) => Err(to_error(state.pos())),
+        Err((_, fail)) => Err(fail),
+    }
+}
+
+/// Like [parse_str], but allows `parser` to leave some of `input` unconsumed.
+pub fn parse_str_allow_trailing<'a, P, A, E>(
+    arena: &'a Bump,
+    parser: P,
+    input: &'a str,
+) -> Result<A, E>
+where
+    P: Parser<'a, A, E>,
+    E: 'a,
+{
+    match parser.parse(arena, State::new(input.as_bytes()), 0) {
+        Ok((_, output, _)) => Ok(output),
+        Err((_, fail)) => Err(fail),
+    }
+}
+
+/// Succeeds only if all input has been consumed. Otherwise fails with `to_error` at the first
+/// unconsumed byte.
+pub fn end_of_input<'a, ToError, E>(to_error: ToError) -> impl Parser<'a, (), E>
+where
+    ToError: Fn(Position) -> E,
+    E: 'a,
+{
+    move |_arena: &'a Bump, state: State<'a>, _min_indent: u32| {
+        if state.has_reached_end() {
+            Ok((NoProgress, (), state))
+        } else {
+            Err((NoProgress, to_error(state.pos())))
+        }
+    }
+}
+
+/// Succeeds without consuming any input, producing the current position.
+pub fn position<'a, E>() -> impl Parser<'a, Position, E>
+where
+    E: 'a,
+{
+    move |_arena: &'a Bump, state: State<'a>, _min_indent: u32| {
+        let pos = state.pos();
+        Ok((NoProgress, pos, state))
+    }
+}
+
+/// Parse a single UTF-8 encoded character, keeping track of newlines.
+/// A `\r\n` sequence is parsed as one `'\n'`.
+pub fn any<'a, ToError, E>(to_error: ToError) -> impl Parser<'a, char, E>
+where
+    ToError: Fn(Position) -> E,
+    E: 'a,
+{
+    use encode_unicode::CharExt;
+
+    move |_arena: &'a Bump, state: State<'a>, _min_indent: u32| match state.bytes() {
+        [b'\n', ..] => Ok((MadeProgress, '\n', state.advance_newline())),
+        [b'\r', b'\n', ..] => Ok((MadeProgress, '\n', state.advance(1).advance_newline())),
+        bytes => match char::from_utf8_slice_start(bytes) {
+            Ok((ch, width)) => Ok((MadeProgress, ch, state.advance(width))),
+            Err(_) => Err((NoProgress, to_error(state.pos()))),
+        },
+    }
+}
+
+/// Look at the next character as [any] would parse it, without consuming any input.
+/// Produces `None` at the end of input, or if the next bytes are not valid UTF-8.
+pub fn peek<'a, E>() -> impl Parser<'a, Option<char>, E>
+where
+    E: 'a,
+{
+    use encode_unicode::CharExt;
+
+    move |_arena: &'a Bump, state: State<'a>, _min_indent: u32| {
+        let ch = match state.bytes() {
+            [b'\r', b'\n', ..] => Some('\n'),
+            bytes => char::from_utf8_slice_start(bytes).ok().map(|(ch, _)| ch),
+        };
+
+        Ok((NoProgress, ch, state))
+    }
+}
+
+/// Parse exactly `count` characters as with [any], returning the source text they span.
+/// Fails without progress if the input ends, or is not valid UTF-8, before that many characters.
+pub fn take<'a, ToError, E>(count: usize, to_error: ToError) -> impl Parser<'a, &'a str, E>
+where
+    ToError: Fn(Position) -> E,
+    E: 'a,
+{
+    let char_parser = any(to_error);
+
+    move |arena: &'a Bump, state: State<'a>, min_indent: u32| {
+        let original_state = state.clone();
+        let mut state = state;
+
+        for _ in 0..count {
+            match char_parser.parse(arena, state, min_indent) {
+                Ok((_, _, next_state)) => state = next_state,
+                Err((_, fail)) => return Err((NoProgress, fail)),
+            }
+        }
+
+        let width = state.pos().offset - original_state.pos().offset;
+        let bytes = &original_state.bytes()[..width as usize];
+
+        // every character was decoded by `any`, so these bytes are valid UTF-8
+        let text = unsafe { std::str::from_utf8_unchecked(bytes) };
+        let progress = Progress::from_consumed(width as usize);
+
+        Ok((progress, text, state))
+    }
+}
+
 pub fn word1<'a, ToError, E>(word: u8, to_error: ToError) -> impl Parser<'a, (), E>
 where
     ToError: Fn(Position) -> E,
@@ -1677,6 +1886,16 @@ macro_rules! zero_or_more {
                         let old_state = state.clone();
                         match $parser.parse(arena, state, min_indent) {
                             Ok((_, next_output, next_state)) => {
+                                // an element that consumes nothing would be parsed forever,
+                                // so stop before it
+                                if next_state.bytes().len() == old_state.bytes().len() {
+                                    let progress = Progress::from_lengths(
+                                        start_bytes_len,
+                                        old_state.bytes().len(),
+                                    );
+                                    return Ok((progress, buf, old_state));
+                                }
+
                                 state = next_state;
                                 buf.push(next_output);
                             }
@@ -1717,11 +1936,13 @@ macro_rules! zero_or_more {
 
 #[macro_export]
 macro_rules! one_or_more {
-    ($parser:expr, $to_error:expr) => {
+    ($parser:expr) => {
         move |arena, state: State<'a>, min_indent: u32| {
             use bumpalo::collections::Vec;
 
-            match $parser.parse(arena, state.clone(), min_indent) {
+            let start_bytes_len = state.bytes().len();
+
+            match $parser.parse(arena, state, min_indent) {
                 Ok((_, first_output, next_state)) => {
                     let mut state = next_state;
                     let mut buf = Vec::with_capacity_in(1, arena);
@@ -1732,6 +1953,16 @@ macro_rules! one_or_more {
                         let old_state = state.clone();
                         match $parser.parse(arena, state, min_indent) {
                             Ok((_, next_output, next_state)) => {
+                                // an element that consumes nothing would be parsed forever,
+                                // so stop before it
+                                if next_state.bytes().len() == old_state.bytes().len() {
+                                    let progress = Progress::from_lengths(
+                                        start_bytes_len,
+                                        old_state.bytes().len(),
+                                    );
+                                    return Ok((progress, buf, old_state));
+                                }
+
                                 state = next_state;
                                 buf.push(next_output);
                             }
@@ -1744,7 +1975,7 @@ macro_rules! one_or_more {
                         }
                     }
                 }
-                Err((progress, _)) => Err((progress, $to_error(state.pos()))),
+                Err((progress, fail)) => Err((progress, fail)),
             }
         }
     };
@@ -1846,3 +2077,757 @@ where
         Err((_, f)) => Err((NoProgress, f)),
     }
 }
+
+/// The opposite of [backtrackable]: a failure of `parser` is reported as having made progress,
+/// even if it consumed nothing. Within a [one_of!], this stops later alternatives from being
+/// tried once parsing has reached this point, so the error stays specific to this branch.
+pub fn commit<'a, P, Val, Error>(parser: P) -> impl Parser<'a, Val, Error>
+where
+    P: Parser<'a, Val, Error>,
+    Error: 'a,
+{
+    move |arena: &'a Bump, state: State<'a>, min_indent: u32| match parser
+        .parse(arena, state, min_indent)
+    {
+        Ok(good) => Ok(good),
+        Err((_, f)) => Err((MadeProgress, f)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_parser<'a, T>(
+        arena: &'a Bump,
+        parser: impl Parser<'a, T, Position>,
+        input: &'a str,
+    ) -> ParseResult<'a, T, Position> {
+        parser.parse(arena, State::new(input.as_bytes()), 0)
+    }
+
+    /// `ab | ac | d`, where the error is the position at which parsing failed.
+    fn ab_or_ac_or_d<'a>() -> impl Parser<'a, &'static str, Position> {
+        one_of!(
+            map!(
+                backtrackable(and(word1(b'a', |p| p), word1(b'b', |p| p))),
+                |_| "ab"
+            ),
+            map!(and(word1(b'a', |p| p), word1(b'c', |p| p)), |_| "ac"),
+            map!(word1(b'd', |p| p), |_| "d"),
+        )
+    }
+
+    #[test]
+    fn one_of_first_match() {
+        let arena = Bump::new();
+        let (_, output, state) = run_parser(&arena, ab_or_ac_or_d(), "ab").unwrap();
+
+        assert_eq!(output, "ab");
+        assert!(state.has_reached_end());
+    }
+
+    #[test]
+    fn one_of_last_match() {
+        let arena = Bump::new();
+        let (progress, output, state) = run_parser(&arena, ab_or_ac_or_d(), "d").unwrap();
+
+        assert_eq!((progress, output), (MadeProgress, "d"));
+        assert!(state.has_reached_end());
+    }
+
+    #[test]
+    fn one_of_all_fail_without_progress() {
+        let arena = Bump::new();
+        let result = run_parser(&arena, ab_or_ac_or_d(), "x");
+
+        assert_eq!(result.unwrap_err(), (NoProgress, Position::new(0)));
+    }
+
+    #[test]
+    fn one_of_reports_failure_that_made_progress() {
+        let arena = Bump::new();
+
+        // `ac` consumes the `a` before failing, so its error wins over the later alternatives.
+        let result = run_parser(&arena, ab_or_ac_or_d(), "ax");
+
+        assert_eq!(result.unwrap_err(), (MadeProgress, Position::new(1)));
+    }
+
+    /// `ab | ac | d`, committing to the first alternative once the `a` has been seen.
+    fn committed_ab_or_ac_or_d<'a>() -> impl Parser<'a, &'static str, Position> {
+        one_of!(
+            map!(
+                and(
+                    backtrackable(word1(b'a', |p| p)),
+                    commit(word1(b'b', |p| p))
+                ),
+                |_| "ab"
+            ),
+            map!(and(word1(b'a', |p| p), word1(b'c', |p| p)), |_| "ac"),
+            map!(word1(b'd', |p| p), |_| "d"),
+        )
+    }
+
+    #[test]
+    fn one_of_reports_committed_failure() {
+        let arena = Bump::new();
+
+        // `ac` would match, but the first alternative committed after its `a`.
+        let result = run_parser(&arena, committed_ab_or_ac_or_d(), "ac");
+
+        assert_eq!(result.unwrap_err(), (MadeProgress, Position::new(1)));
+    }
+
+    #[test]
+    fn one_of_tries_next_alternative_before_commit() {
+        let arena = Bump::new();
+        let (_, output, state) = run_parser(&arena, committed_ab_or_ac_or_d(), "d").unwrap();
+
+        assert_eq!(output, "d");
+        assert!(state.has_reached_end());
+    }
+
+    #[test]
+    fn take_counts_characters() {
+        let arena = Bump::new();
+
+        let (_, text, state) = run_parser(&arena, take(3, |p| p), "abcd").unwrap();
+        assert_eq!((text, state.bytes()), ("abc", &b"d"[..]));
+
+        let (_, text, state) = run_parser(&arena, take(2, |p| p), "héllo").unwrap();
+        assert_eq!((text, state.bytes()), ("hé", &b"llo"[..]));
+    }
+
+    #[test]
+    fn take_tracks_newlines() {
+        let arena = Bump::new();
+        let (_, text, state) = run_parser(&arena, take(3, |p| p), "a\nbc").unwrap();
+
+        assert_eq!(text, "a\nb");
+        assert_eq!(state.column(), 1);
+    }
+
+    #[test]
+    fn take_fails_at_end_of_input() {
+        let arena = Bump::new();
+        let result = run_parser(&arena, take(3, |p| p), "ab");
+
+        assert_eq!(result.unwrap_err(), (NoProgress, Position::new(2)));
+    }
+
+    /// A single digit `n`, followed by exactly `n` bytes which are returned.
+    fn length_prefixed<'a>() -> impl Parser<'a, &'a [u8], Position> {
+        let digit = |_: &'a Bump, state: State<'a>, _: u32| match state.bytes().first() {
+            Some(b) if b.is_ascii_digit() => {
+                Ok((MadeProgress, (b - b'0') as usize, state.advance(1)))
+            }
+            _ => Err((NoProgress, state.pos())),
+        };
+
+        and_then(digit, |_, len| {
+            move |_: &'a Bump, state: State<'a>, _: u32| match state.bytes().get(..len) {
+                Some(token) => Ok((MadeProgress, token, state.advance(len))),
+                None => Err((MadeProgress, state.pos())),
+            }
+        })
+    }
+
+    #[test]
+    fn and_then_uses_first_output() {
+        let arena = Bump::new();
+        let (_, token, state) = run_parser(&arena, length_prefixed(), "3abcd").unwrap();
+
+        assert_eq!(token, b"abc");
+        assert_eq!(state.pos(), Position::new(4));
+    }
+
+    #[test]
+    fn and_then_first_parser_fails() {
+        let arena = Bump::new();
+        let result = run_parser(&arena, length_prefixed(), "abc");
+
+        assert_eq!(result.unwrap_err(), (NoProgress, Position::new(0)));
+    }
+
+    #[test]
+    fn and_then_second_parser_fails() {
+        let arena = Bump::new();
+        let result = run_parser(&arena, length_prefixed(), "5abc");
+
+        assert_eq!(result.unwrap_err(), (MadeProgress, Position::new(1)));
+    }
+
+    #[test]
+    fn any_ascii() {
+        let arena = Bump::new();
+        let (_, ch, state) = run_parser(&arena, any(|p| p), "ab").unwrap();
+
+        assert_eq!(ch, 'a');
+        assert_eq!(state.pos(), Position::new(1));
+        assert_eq!(state.column(), 1);
+    }
+
+    #[test]
+    fn any_multi_byte() {
+        let arena = Bump::new();
+        let (_, ch, state) = run_parser(&arena, any(|p| p), "éa").unwrap();
+
+        assert_eq!(ch, 'é');
+        assert_eq!(state.pos(), Position::new('é'.len_utf8() as u32));
+        assert_eq!(state.bytes(), b"a");
+    }
+
+    #[test]
+    fn any_newline() {
+        let arena = Bump::new();
+
+        for input in ["\na", "\r\na"] {
+            let (_, ch, state) = run_parser(&arena, any(|p| p), input).unwrap();
+
+            assert_eq!(ch, '\n');
+            assert_eq!(state.bytes(), b"a");
+            assert_eq!(state.column(), 0);
+        }
+    }
+
+    #[test]
+    fn any_lone_carriage_return() {
+        let arena = Bump::new();
+        let (_, ch, state) = run_parser(&arena, any(|p| p), "\ra").unwrap();
+
+        assert_eq!(ch, '\r');
+        assert_eq!(state.column(), 1);
+    }
+
+    #[test]
+    fn any_at_end_of_input() {
+        let arena = Bump::new();
+        let result = run_parser(&arena, any(|p| p), "");
+
+        assert_eq!(result.unwrap_err(), (NoProgress, Position::new(0)));
+    }
+
+    #[test]
+    fn peek_does_not_advance() {
+        let arena = Bump::new();
+
+        for (input, expected) in [("ab", 'a'), ("éa", 'é'), ("\r\na", '\n')] {
+            let (progress, ch, state) = run_parser(&arena, peek(), input).unwrap();
+
+            assert_eq!(progress, NoProgress);
+            assert_eq!(ch, Some(expected));
+            assert_eq!(state.pos(), Position::new(0));
+            assert_eq!(state.bytes(), input.as_bytes());
+        }
+    }
+
+    #[test]
+    fn peek_at_end_of_input() {
+        let arena = Bump::new();
+        let (progress, ch, _) = run_parser(&arena, peek(), "").unwrap();
+
+        assert_eq!(progress, NoProgress);
+        assert_eq!(ch, None);
+    }
+
+    #[test]
+    fn peek_then_any_agree() {
+        let arena = Bump::new();
+        let parser = and(peek(), any(|p| p));
+        let (_, (peeked, parsed), state) = run_parser(&arena, parser, "xy").unwrap();
+
+        assert_eq!(peeked, Some(parsed));
+        assert_eq!(state.bytes(), b"y");
+    }
+
+    #[test]
+    fn parse_str_consumes_everything() {
+        let arena = Bump::new();
+
+        assert_eq!(parse_str(&arena, ab_or_ac_or_d(), "ac", |p| p), Ok("ac"));
+    }
+
+    #[test]
+    fn parse_str_rejects_trailing_input() {
+        let arena = Bump::new();
+
+        assert_eq!(
+            parse_str(&arena, ab_or_ac_or_d(), "ac!", |p| p),
+            Err(Position::new(2))
+        );
+    }
+
+    #[test]
+    fn parse_str_reports_parser_failure() {
+        let arena = Bump::new();
+
+        assert_eq!(
+            parse_str(&arena, ab_or_ac_or_d(), "ax", |_| Position::new(99)),
+            Err(Position::new(1))
+        );
+    }
+
+    #[test]
+    fn parse_str_allow_trailing_ignores_leftover_input() {
+        let arena = Bump::new();
+
+        assert_eq!(
+            parse_str_allow_trailing(&arena, ab_or_ac_or_d(), "ac!"),
+            Ok("ac")
+        );
+    }
+
+    fn digit_then_end<'a>() -> impl Parser<'a, u8, Position> {
+        skip_second!(digit(), end_of_input(|p| p))
+    }
+
+    #[test]
+    fn end_of_input_at_end() {
+        let arena = Bump::new();
+        let (progress, (), state) = run_parser(&arena, end_of_input(|p| p), "").unwrap();
+
+        assert_eq!(progress, NoProgress);
+        assert!(state.has_reached_end());
+
+        let (_, output, _) = run_parser(&arena, digit_then_end(), "7").unwrap();
+        assert_eq!(output, 7);
+    }
+
+    #[test]
+    fn end_of_input_with_input_left() {
+        let arena = Bump::new();
+
+        let result = run_parser(&arena, end_of_input(|p| p), "x");
+        assert_eq!(result.unwrap_err(), (NoProgress, Position::new(0)));
+
+        let result = run_parser(&arena, digit_then_end(), "78");
+        assert_eq!(result.unwrap_err(), (MadeProgress, Position::new(1)));
+    }
+
+    #[test]
+    fn position_after_advancing() {
+        fn after_two_chars<'a>() -> impl Parser<'a, Position, Position> {
+            skip_first!(and(any(|p| p), any(|p| p)), position())
+        }
+
+        let arena = Bump::new();
+        let (_, output, state) = run_parser(&arena, after_two_chars(), "a\nb").unwrap();
+
+        assert_eq!(output, Position::new(2));
+        assert_eq!(state.column(), 0);
+        assert_eq!(state.bytes(), b"b");
+    }
+
+    #[test]
+    fn position_consumes_nothing() {
+        let arena = Bump::new();
+        let (progress, output, state) = run_parser(&arena, position(), "ab").unwrap();
+
+        assert_eq!((progress, output), (NoProgress, Position::new(0)));
+        assert_eq!(state.bytes(), b"ab");
+    }
+
+    #[test]
+    fn map_with_arena_allocates() {
+        let arena = Bump::new();
+        let parser = map_with_arena(digit(), |arena: &Bump, d| &*arena.alloc(d));
+        let (_, output, state) = run_parser(&arena, parser, "7").unwrap();
+
+        assert_eq!(output, &7);
+        assert!(state.has_reached_end());
+    }
+
+    #[test]
+    fn not_followed_by_succeeds_when_parser_fails() {
+        let arena = Bump::new();
+        let parser = not_followed_by(|p| p, word1(b'a', |p| p));
+        let (progress, (), state) = run_parser(&arena, parser, "b").unwrap();
+
+        assert_eq!(progress, NoProgress);
+        assert_eq!(state.bytes(), b"b");
+    }
+
+    #[test]
+    fn not_followed_by_fails_when_parser_succeeds() {
+        let arena = Bump::new();
+        let result = run_parser(&arena, not_followed_by(|p| p, word1(b'a', |p| p)), "a");
+
+        assert_eq!(result.unwrap_err(), (NoProgress, Position::new(0)));
+    }
+
+    #[test]
+    fn not_followed_by_restores_state_after_partial_match() {
+        let arena = Bump::new();
+        let parser = not_followed_by(|p| p, ab_or_ac_or_d());
+        let (progress, (), state) = run_parser(&arena, parser, "ax").unwrap();
+
+        assert_eq!(progress, NoProgress);
+        assert_eq!(state.bytes(), b"ax");
+    }
+
+    #[test]
+    fn optional_some() {
+        let arena = Bump::new();
+        let (_, output, state) = run_parser(&arena, optional(ab_or_ac_or_d()), "ac!").unwrap();
+
+        assert_eq!(output, Some("ac"));
+        assert_eq!(state.bytes(), b"!");
+    }
+
+    #[test]
+    fn optional_none_consumes_nothing() {
+        let arena = Bump::new();
+        let (progress, output, state) =
+            run_parser(&arena, optional(ab_or_ac_or_d()), "x!").unwrap();
+
+        assert_eq!((progress, output), (NoProgress, None));
+        assert_eq!(state.pos(), Position::new(0));
+    }
+
+    #[test]
+    fn optional_keeps_committed_failure() {
+        let arena = Bump::new();
+
+        // Having consumed the `a`, the inner parser is committed; its error must not be
+        // swallowed into a `None` that would leave the caller half-way through the input.
+        let result = run_parser(&arena, optional(ab_or_ac_or_d()), "ax");
+
+        assert_eq!(result.unwrap_err(), (MadeProgress, Position::new(1)));
+    }
+
+    fn digit<'a>() -> impl Parser<'a, u8, Position> {
+        |_: &'a Bump, state: State<'a>, _: u32| match state.bytes().first() {
+            Some(b) if b.is_ascii_digit() => Ok((MadeProgress, b - b'0', state.advance(1))),
+            _ => Err((NoProgress, state.pos())),
+        }
+    }
+
+    #[test]
+    fn sep_by_zero_elements() {
+        let arena = Bump::new();
+
+        let (progress, output, state) =
+            run_parser(&arena, sep_by0(word1(b',', |p| p), digit()), "!").unwrap();
+        assert_eq!((progress, output.as_slice()), (NoProgress, &[][..]));
+        assert_eq!(state.pos(), Position::new(0));
+
+        let result = run_parser(&arena, sep_by1(word1(b',', |p| p), digit()), "!");
+        assert_eq!(result.unwrap_err(), (NoProgress, Position::new(0)));
+    }
+
+    #[test]
+    fn sep_by_one_element() {
+        let arena = Bump::new();
+
+        for output in [
+            run_parser(&arena, sep_by0(word1(b',', |p| p), digit()), "1!"),
+            run_parser(&arena, sep_by1(word1(b',', |p| p), digit()), "1!"),
+        ] {
+            let (_, output, state) = output.unwrap();
+            assert_eq!(output.as_slice(), &[1]);
+            assert_eq!(state.bytes(), b"!");
+        }
+    }
+
+    #[test]
+    fn sep_by_several_elements() {
+        let arena = Bump::new();
+
+        for output in [
+            run_parser(&arena, sep_by0(word1(b',', |p| p), digit()), "1,2,3!"),
+            run_parser(&arena, sep_by1(word1(b',', |p| p), digit()), "1,2,3!"),
+        ] {
+            let (_, output, state) = output.unwrap();
+            assert_eq!(output.as_slice(), &[1, 2, 3]);
+            assert_eq!(state.bytes(), b"!");
+        }
+    }
+
+    #[test]
+    fn sep_by_rejects_trailing_separator() {
+        let arena = Bump::new();
+
+        let result = run_parser(&arena, sep_by0(word1(b',', |p| p), digit()), "1,2,!");
+        assert_eq!(result.unwrap_err(), (MadeProgress, Position::new(4)));
+
+        let result = run_parser(&arena, sep_by1(word1(b',', |p| p), digit()), "1,2,!");
+        assert_eq!(result.unwrap_err(), (MadeProgress, Position::new(4)));
+    }
+
+    fn digits<'a>() -> impl Parser<'a, Vec<'a, u8>, Position> {
+        zero_or_more!(digit())
+    }
+
+    #[test]
+    fn zero_or_more_empty() {
+        let arena = Bump::new();
+        let (progress, output, state) = run_parser(&arena, digits(), "!").unwrap();
+
+        assert_eq!((progress, output.as_slice()), (NoProgress, &[][..]));
+        assert_eq!(state.bytes(), b"!");
+    }
+
+    #[test]
+    fn zero_or_more_one() {
+        let arena = Bump::new();
+        let (progress, output, state) = run_parser(&arena, digits(), "1!").unwrap();
+
+        assert_eq!((progress, output.as_slice()), (MadeProgress, &[1][..]));
+        assert_eq!(state.bytes(), b"!");
+    }
+
+    #[test]
+    fn zero_or_more_many() {
+        let arena = Bump::new();
+        let (progress, output, state) = run_parser(&arena, digits(), "123").unwrap();
+
+        assert_eq!(
+            (progress, output.as_slice()),
+            (MadeProgress, &[1, 2, 3][..])
+        );
+        assert!(state.has_reached_end());
+    }
+
+    #[test]
+    fn zero_or_more_stops_when_element_consumes_nothing() {
+        fn nothing<'a>() -> impl Parser<'a, Vec<'a, ()>, Position> {
+            zero_or_more!(|_: &'a Bump, state: State<'a>, _: u32| Ok((NoProgress, (), state)))
+        }
+
+        let arena = Bump::new();
+        let (progress, output, state) = run_parser(&arena, nothing(), "1").unwrap();
+
+        assert_eq!((progress, output.len()), (NoProgress, 1));
+        assert_eq!(state.bytes(), b"1");
+    }
+
+    #[test]
+    fn one_or_more_reports_first_element_failure() {
+        fn many_a<'a>() -> impl Parser<'a, Vec<'a, ()>, Position> {
+            one_or_more!(word1(b'a', |_| Position::new(42)))
+        }
+
+        fn many_ab_ac_d<'a>() -> impl Parser<'a, Vec<'a, &'static str>, Position> {
+            one_or_more!(ab_or_ac_or_d())
+        }
+
+        let arena = Bump::new();
+
+        let result = run_parser(&arena, many_a(), "b");
+        assert_eq!(result.unwrap_err(), (NoProgress, Position::new(42)));
+
+        let result = run_parser(&arena, many_ab_ac_d(), "ax");
+        assert_eq!(result.unwrap_err(), (MadeProgress, Position::new(1)));
+    }
+
+    #[test]
+    fn one_or_more_stops_when_element_consumes_nothing() {
+        fn nothing<'a>() -> impl Parser<'a, Vec<'a, ()>, Position> {
+            fn zero_width<'a>(
+                _: &'a Bump,
+                state: State<'a>,
+                _: u32,
+            ) -> ParseResult<'a, (), Position> {
+                Ok((NoProgress, (), state))
+            }
+
+            one_or_more!(zero_width)
+        }
+
+        let arena = Bump::new();
+        let (progress, output, state) = run_parser(&arena, nothing(), "1").unwrap();
+
+        assert_eq!((progress, output.len()), (NoProgress, 1));
+        assert_eq!(state.bytes(), b"1");
+    }
+
+    fn skip_open_paren<'a>() -> impl Parser<'a, u8, Position> {
+        skip_first!(word1(b'(', |p| p), digit())
+    }
+
+    fn skip_close_paren<'a>() -> impl Parser<'a, u8, Position> {
+        skip_second!(digit(), word1(b')', |p| p))
+    }
+
+    #[test]
+    fn skip_first_advances_past_both() {
+        let arena = Bump::new();
+        let (progress, output, state) = run_parser(&arena, skip_open_paren(), "(1!").unwrap();
+
+        assert_eq!((progress, output), (MadeProgress, 1));
+        assert_eq!(state.bytes(), b"!");
+    }
+
+    #[test]
+    fn skip_second_advances_past_both() {
+        let arena = Bump::new();
+        let (progress, output, state) = run_parser(&arena, skip_close_paren(), "1)!").unwrap();
+
+        assert_eq!((progress, output), (MadeProgress, 1));
+        assert_eq!(state.bytes(), b"!");
+    }
+
+    #[test]
+    fn skip_fails_if_either_fails() {
+        let arena = Bump::new();
+
+        let result = run_parser(&arena, skip_open_paren(), "1");
+        assert_eq!(result.unwrap_err(), (NoProgress, Position::new(0)));
+        let result = run_parser(&arena, skip_open_paren(), "(x");
+        assert_eq!(result.unwrap_err(), (MadeProgress, Position::new(1)));
+
+        let result = run_parser(&arena, skip_close_paren(), ")");
+        assert_eq!(result.unwrap_err(), (NoProgress, Position::new(0)));
+        let result = run_parser(&arena, skip_close_paren(), "1x");
+        assert_eq!(result.unwrap_err(), (MadeProgress, Position::new(1)));
+    }
+
+    #[test]
+    fn keyword_matches_whole_word() {
+        let arena = Bump::new();
+
+        for input in ["if x", "if\nx", "if#x", "if"] {
+            let (progress, (), state) = run_parser(&arena, keyword_e("if", |p| p), input).unwrap();
+
+            assert_eq!(progress, MadeProgress);
+            assert_eq!(state.pos(), Position::new(2));
+        }
+    }
+
+    #[test]
+    fn keyword_rejects_longer_identifier() {
+        let arena = Bump::new();
+
+        for input in ["iffy", "if_x", "if2"] {
+            let result = run_parser(&arena, keyword_e("if", |p| p), input);
+
+            assert_eq!(result.unwrap_err(), (NoProgress, Position::new(0)));
+        }
+    }
+
+    fn parenthesized_digit<'a>() -> impl Parser<'a, u8, Position> {
+        between!(word1(b'(', |p| p), digit(), word1(b')', |p| p))
+    }
+
+    #[test]
+    fn between_keeps_inner_output() {
+        let arena = Bump::new();
+        let (_, output, state) = run_parser(&arena, parenthesized_digit(), "(7)!").unwrap();
+
+        assert_eq!(output, 7);
+        assert_eq!(state.bytes(), b"!");
+    }
+
+    #[test]
+    fn between_missing_close() {
+        let arena = Bump::new();
+
+        // The error points at where `)` was expected, not back at the `(`.
+        let result = run_parser(&arena, parenthesized_digit(), "(7!");
+
+        assert_eq!(result.unwrap_err(), (MadeProgress, Position::new(2)));
+    }
+
+    #[test]
+    fn and_keeps_both_outputs() {
+        let arena = Bump::new();
+        let (progress, output, state) = run_parser(&arena, and(digit(), digit()), "12!").unwrap();
+
+        assert_eq!((progress, output), (MadeProgress, (1, 2)));
+        assert_eq!(state.bytes(), b"!");
+    }
+
+    #[test]
+    fn nested_and() {
+        let arena = Bump::new();
+        let parser = map!(and(digit(), and(digit(), digit())), |(a, (b, c))| (a, b, c));
+        let (_, output, state) = run_parser(&arena, parser, "123!").unwrap();
+
+        assert_eq!(output, (1, 2, 3));
+        assert_eq!(state.bytes(), b"!");
+    }
+
+    /// The state just after the indentation of the second line of `"x\n   1"`.
+    fn indented_by_three(input: &str) -> State<'_> {
+        State::new(input.as_bytes())
+            .advance(1)
+            .advance_newline()
+            .advance(3)
+            .mark_current_indent()
+    }
+
+    #[test]
+    fn indented_more_than_accepts_deeper_lines() {
+        let arena = Bump::new();
+        let parser = indented_more_than(2, |p| p, digit());
+        let (progress, output, state) = parser
+            .parse(&arena, indented_by_three("x\n   1"), 0)
+            .unwrap();
+
+        assert_eq!((progress, output), (MadeProgress, 1));
+        assert!(state.has_reached_end());
+    }
+
+    #[test]
+    fn indented_more_than_rejects_equal_indentation() {
+        let arena = Bump::new();
+        let parser = indented_more_than(3, |p| p, digit());
+        let result = parser.parse(&arena, indented_by_three("x\n   1"), 0);
+
+        assert_eq!(result.unwrap_err(), (NoProgress, Position::new(5)));
+    }
+
+    #[test]
+    fn indented_more_than_rejects_shallower_lines() {
+        let arena = Bump::new();
+        let parser = indented_more_than(4, |p| p, digit());
+        let result = parser.parse(&arena, indented_by_three("x\n   1"), 0);
+
+        assert_eq!(result.unwrap_err(), (NoProgress, Position::new(5)));
+    }
+
+    #[test]
+    fn at_column_accepts_exact_column() {
+        let arena = Bump::new();
+        let parser = at_column(3, |p| p, digit());
+        let (_, output, state) = parser
+            .parse(&arena, indented_by_three("x\n   1"), 0)
+            .unwrap();
+
+        assert_eq!(output, 1);
+        assert!(state.has_reached_end());
+    }
+
+    #[test]
+    fn at_column_rejects_other_columns() {
+        let arena = Bump::new();
+
+        for column in [2, 4] {
+            let parser = at_column(column, |p| p, digit());
+            let result = parser.parse(&arena, indented_by_three("x\n   1"), 0);
+
+            assert_eq!(result.unwrap_err(), (NoProgress, Position::new(5)));
+        }
+    }
+
+    #[test]
+    fn with_indent_captures_starting_indentation() {
+        let arena = Bump::new();
+        let start = indented_by_three("x\n   1");
+        let column = start.column();
+
+        let (_, (indent, output), state) = with_indent(digit()).parse(&arena, start, 0).unwrap();
+
+        assert_eq!((indent, output), (column, 1));
+        assert!(state.has_reached_end());
+    }
+
+    #[test]
+    fn and_reports_second_failure() {
+        let arena = Bump::new();
+        let result = run_parser(&arena, and(digit(), digit()), "1x");
+
+        assert_eq!(result.unwrap_err(), (MadeProgress, Position::new(1)));
+    }
+}
@@ -1,8 +1,46 @@
+use bumpalo::Bump;
 use roc_region::all::{Position, Region};
 use std::fmt;
+use std::ops::Range;
 
 use crate::parser::Progress;
 
+/// Source text that can be parsed.
+///
+/// The parser works on a contiguous slice of bytes, so contiguous inputs are parsed in place,
+/// while others (like ropes) are copied into the arena once, up front.
+pub trait ParseInput<'a> {
+    /// The length of the input in bytes.
+    fn len(&self) -> usize;
+
+    /// The bytes in `range`, either borrowed from the input or copied into `arena`.
+    fn get(&self, arena: &'a Bump, range: Range<usize>) -> &'a [u8];
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<'a> ParseInput<'a> for &'a str {
+    fn len(&self) -> usize {
+        str::len(self)
+    }
+
+    fn get(&self, _arena: &'a Bump, range: Range<usize>) -> &'a [u8] {
+        &self.as_bytes()[range]
+    }
+}
+
+impl<'a> ParseInput<'a> for &'a [u8] {
+    fn len(&self) -> usize {
+        <[u8]>::len(self)
+    }
+
+    fn get(&self, _arena: &'a Bump, range: Range<usize>) -> &'a [u8] {
+        &self[range]
+    }
+}
+
 /// A position in a source file.
 // NB: [Copy] is explicitly NOT derived to reduce the chance of bugs due to accidentally re-using
 // parser state.
@@ -36,6 +74,10 @@ impl<'a> State<'a> {
         }
     }
 
+    pub fn from_input(arena: &'a Bump, input: impl ParseInput<'a>) -> State<'a> {
+        State::new(input.get(arena, 0..input.len()))
+    }
+
     pub fn original_bytes(&self) -> &'a [u8] {
         self.original_bytes
     }
@@ -153,3 +195,43 @@ fn state_size() {
     let maximum = std::mem::size_of::<usize>() * 8;
     assert!(state_size <= maximum, "{state_size:?} <= {maximum:?}");
 }
+
+#[test]
+fn parse_from_custom_input() {
+    use crate::module::module_defs;
+    use crate::parser::Parser;
+
+    /// A source split into chunks, like a rope.
+    struct Chunks<'s>(&'s [&'s str]);
+
+    impl<'a> ParseInput<'a> for Chunks<'a> {
+        fn len(&self) -> usize {
+            self.0.iter().map(|chunk| chunk.len()).sum()
+        }
+
+        fn get(&self, arena: &'a Bump, range: Range<usize>) -> &'a [u8] {
+            let mut bytes = bumpalo::collections::Vec::new_in(arena);
+            for chunk in self.0 {
+                bytes.extend_from_slice(chunk.as_bytes());
+            }
+
+            &bytes.into_bump_slice()[range]
+        }
+    }
+
+    let arena = Bump::new();
+    let src = "x = 1\ny = \"two\"\n";
+    let chunks = Chunks(&["x =", " 1\ny", " = \"t", "wo\"\n"]);
+
+    let parse = |state| {
+        module_defs()
+            .parse(&arena, state, 0)
+            .map(|(_, defs, _)| defs)
+    };
+
+    let from_str = parse(State::from_input(&arena, src));
+    let from_chunks = parse(State::from_input(&arena, chunks));
+
+    assert!(from_str.is_ok());
+    assert_eq!(from_str, from_chunks);
+}
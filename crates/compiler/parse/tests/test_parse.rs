@@ -227,6 +227,12 @@ mod test_parse {
         });
     }
 
+    #[test]
+    fn string_with_tab() {
+        // Tabs are only rejected where they could be mistaken for indentation.
+        assert_parses_to("\"a\tb\"", Str(PlainLine("a\tb")));
+    }
+
     #[test]
     fn empty_source_file() {
         assert_parsing_fails("", SyntaxError::Eof(Region::zero()));
@@ -252,6 +258,23 @@ mod test_parse {
         assert_parses_to(float_string.as_str(), Float(float_string.as_str()));
     }
 
+    #[test]
+    fn number_literals() {
+        assert_parses_to("1_000", Num("1_000"));
+        assert_parses_to("-42", Num("-42"));
+        assert_parses_to("-0.5", Float("-0.5"));
+        assert_parses_to("1.0", Float("1.0"));
+        assert_parses_to("1.", Float("1."));
+        assert_parses_to("12e-3", Float("12e-3"));
+    }
+
+    #[test]
+    fn repeated_decimal_points_are_left_to_canonicalization() {
+        // The parser takes the whole literal so that canonicalization can report it as one
+        // invalid float, rather than as a float followed by something unexpected.
+        assert_parses_to("1..2", Float("1..2"));
+    }
+
     // SINGLE QUOTE LITERAL
     #[test]
     fn single_quote() {
@@ -476,3 +476,32 @@ fn test_line_info() {
 
     check_correctness(&["", ""]);
 }
+
+#[test]
+fn test_span_across() {
+    let info = LineInfo::new("ab cd\nef");
+    let at = |line, column| LineColumn { line, column };
+
+    let ab = Region::new(Position::new(0), Position::new(2));
+    let cd = Region::new(Position::new(3), Position::new(5));
+    let ef = Region::new(Position::new(6), Position::new(8));
+
+    let same_line = Region::span_across(&ab, &cd);
+    assert_eq!(same_line, Region::new(Position::new(0), Position::new(5)));
+    assert_eq!(
+        info.convert_region(same_line),
+        LineColumnRegion::new(at(0, 0), at(0, 5))
+    );
+
+    let cross_line = Region::span_across(&cd, &ef);
+    assert_eq!(cross_line, Region::new(Position::new(3), Position::new(8)));
+    assert_eq!(
+        info.convert_region(cross_line),
+        LineColumnRegion::new(at(0, 3), at(1, 2))
+    );
+
+    assert_eq!(
+        LineColumnRegion::span_across(&info.convert_region(cd), &info.convert_region(ef)),
+        info.convert_region(cross_line)
+    );
+}
@@ -510,6 +510,7 @@ fn solve(
                         vars,
                         must_implement_ability,
                         lambda_sets_to_specialize,
+                        changed: _,
                         extra_metadata: _,
                     } => {
                         env.introduce(rank, &vars);
@@ -621,6 +622,7 @@ fn solve(
                                 vars,
                                 must_implement_ability,
                                 lambda_sets_to_specialize,
+                                changed: _,
                                 extra_metadata: _,
                             } => {
                                 env.introduce(rank, &vars);
@@ -728,6 +730,7 @@ fn solve(
                         vars,
                         must_implement_ability,
                         lambda_sets_to_specialize,
+                        changed: _,
                         extra_metadata: _,
                     } => {
                         env.introduce(rank, &vars);
@@ -940,6 +943,7 @@ fn solve(
                         vars,
                         must_implement_ability,
                         lambda_sets_to_specialize,
+                        changed: _,
                         extra_metadata: _,
                     } => {
                         env.introduce(rank, &vars);
@@ -1079,6 +1083,7 @@ fn solve(
                         vars,
                         must_implement_ability,
                         lambda_sets_to_specialize,
+                        changed: _,
                         extra_metadata: _,
                     } => {
                         env.subs.commit_snapshot(snapshot);
@@ -1329,6 +1334,7 @@ fn solve(
                     vars,
                     must_implement_ability,
                     lambda_sets_to_specialize,
+                    changed: _,
                     extra_metadata: _,
                 } = unify(
                     &mut env.uenv(),
@@ -1361,6 +1367,7 @@ fn solve(
                             vars,
                             must_implement_ability,
                             lambda_sets_to_specialize,
+                            changed: _,
                             extra_metadata: _,
                         } => {
                             env.introduce(rank, &vars);
@@ -1613,6 +1620,7 @@ fn check_ability_specialization(
                 vars,
                 must_implement_ability,
                 lambda_sets_to_specialize,
+                changed: _,
                 extra_metadata: SpecializationLsetCollector(specialization_lambda_sets),
             } => {
                 let specialization_type =
@@ -796,6 +796,7 @@ pub(crate) fn type_to_var_help(
                         vars: _,
                         must_implement_ability,
                         lambda_sets_to_specialize,
+                        changed: _,
                         extra_metadata: _,
                     } => {
                         // No introduction needed
@@ -15,27 +15,74 @@ bitflags! {
         /// specialization algorithm is running. This has implications for the unification of
         /// unspecialized lambda sets; see [`unify_unspecialized_lambdas`].
         const LAMBDA_SET_SPECIALIZATION = UnificationMode::EQ.bits | (1 << 2);
+        /// Like [`UnificationMode::PRESENT`], but forbids the left-hand-side tag union from
+        /// growing new top-level tags. Nested tag unions in payloads may still grow.
+        ///
+        /// For example, [A] += [A, B] is a mismatch under this mode.
+        const NO_OPEN = UnificationMode::PRESENT.bits | (1 << 3);
+        /// May be combined with any other mode to have the unifier report where each type
+        /// mismatch was raised, as a `Mismatch::TypeMismatchSource`. Intended for tests.
+        const RECORD_MISMATCH_SOURCE = 1 << 4;
+        /// May be combined with any other mode to forbid the right-hand-side of a record
+        /// unification from having fields that are not on the left-hand-side, even if the
+        /// left-hand-side record is open.
+        ///
+        /// For example, { x : Str }a ~ { x : Str, y : Str } is a mismatch under this mode.
+        const CLOSED_RECORD = 1 << 5;
+        /// May be combined with any other mode to have the unifier report the mismatches of every
+        /// record field and argument that fails to unify, rather than only that some did. Each
+        /// failed sub-unification is rolled back, so the others are checked independently.
+        const COLLECT_ALL = 1 << 6;
     }
 }
 
 impl UnificationMode {
+    /// Checks that this mode does not ask for both [UnificationMode::EQ] and
+    /// [UnificationMode::PRESENT], which are mutually exclusive. Only checked in debug builds, by
+    /// the public entry points of the unifier.
+    pub fn debug_validate(&self) {
+        debug_assert!(
+            !self.contains(UnificationMode::EQ | UnificationMode::PRESENT),
+            "{self:?} sets both EQ and PRESENT"
+        );
+    }
+
     pub fn is_eq(&self) -> bool {
-        debug_assert!(!self.contains(UnificationMode::EQ | UnificationMode::PRESENT));
         self.contains(UnificationMode::EQ)
     }
 
     pub fn is_present(&self) -> bool {
-        debug_assert!(!self.contains(UnificationMode::EQ | UnificationMode::PRESENT));
         self.contains(UnificationMode::PRESENT)
     }
 
     pub fn is_lambda_set_specialization(&self) -> bool {
-        debug_assert!(!self.contains(UnificationMode::EQ | UnificationMode::PRESENT));
         self.contains(UnificationMode::LAMBDA_SET_SPECIALIZATION)
     }
 
+    pub fn is_no_open(&self) -> bool {
+        self.contains(UnificationMode::NO_OPEN)
+    }
+
+    pub fn is_closed_record(&self) -> bool {
+        self.contains(UnificationMode::CLOSED_RECORD)
+    }
+
+    pub fn is_collect_all(&self) -> bool {
+        self.contains(UnificationMode::COLLECT_ALL)
+    }
+
     pub fn as_eq(self) -> Self {
-        (self - UnificationMode::PRESENT) | UnificationMode::EQ
+        (self - UnificationMode::NO_OPEN) | UnificationMode::EQ
+    }
+
+    /// The mode to use for unifying the payloads of tags, which are allowed to grow even when
+    /// the enclosing tag union is not.
+    pub fn for_payloads(self) -> Self {
+        if self.is_no_open() {
+            (self - UnificationMode::NO_OPEN) | UnificationMode::PRESENT
+        } else {
+            self
+        }
     }
 
     pub fn pretty_print(&self) -> &str {
@@ -1576,6 +1576,44 @@ fn tail_call_elimination() {
     )
 }
 
+#[mono_test]
+fn tail_call_behind_join_point() {
+    indoc!(
+        r#"
+        sum = \n, accum ->
+            result =
+                when n is
+                    0 -> accum
+                    _ -> sum (n - 1) (n + accum)
+
+            result
+
+        sum 1_000_000 0
+        "#
+    )
+}
+
+#[mono_test]
+fn tail_call_in_nested_switches() {
+    indoc!(
+        r#"
+        count = \a, b, accum ->
+            when a is
+                0 ->
+                    when b is
+                        0 -> accum
+                        _ -> count a (b - 1) (accum + 1)
+
+                _ ->
+                    when b is
+                        0 -> count (a - 1) b (accum + 1)
+                        _ -> count (a - 1) (b - 1) (accum + 2)
+
+        count 10 20 0
+        "#
+    )
+}
+
 #[mono_test]
 fn tail_call_with_same_layout_different_lambda_sets() {
     indoc!(
@@ -3169,6 +3207,26 @@ fn linked_list_map() {
     )
 }
 
+#[mono_test]
+fn linked_list_map_from_list() {
+    indoc!(
+        r#"
+        app "test" provides [main] to "./platform"
+
+        LinkedList a : [Nil, Cons a (LinkedList a)]
+
+        mapFromList : List a, (a -> b) -> LinkedList b
+        mapFromList = \list, f ->
+            when List.first list is
+                Ok x -> Cons (f x) (mapFromList (List.dropFirst list 1) f)
+                Err _ -> Nil
+
+        main : LinkedList I64
+        main = mapFromList [1, 2, 3] (\x -> x + 1i64)
+        "#
+    )
+}
+
 #[mono_test]
 fn linked_list_filter() {
     indoc!(
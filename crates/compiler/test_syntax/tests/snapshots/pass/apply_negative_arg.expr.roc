@@ -0,0 +1 @@
+f -1
\ No newline at end of file
@@ -266,6 +266,7 @@ mod test_snapshots {
         pass/annotated_record_destructure.expr,
         pass/annotated_tag_destructure.expr,
         pass/annotated_tuple_destructure.expr,
+        pass/apply_negative_arg.expr,
         pass/apply_parenthetical_tag_args.expr,
         pass/apply_tag.expr,
         pass/apply_three_args.expr,
@@ -2027,6 +2027,26 @@ impl Subs {
         result
     }
 
+    /// Like [Subs::occurs], but if `var` does not occur recursively, `acyclic` is extended with
+    /// the roots of every variable reachable from `var`. None of those occur recursively either,
+    /// so callers may use them to skip later occurs checks until `subs` is next modified.
+    pub fn occurs_recording_acyclic(
+        &mut self,
+        var: Variable,
+        acyclic: &mut impl Extend<Variable>,
+    ) -> Result<(), (Variable, Vec<Variable>)> {
+        let mut scratchpad = take_occurs_scratchpad();
+        let result = occurs(self, &mut scratchpad, var);
+        for v in &scratchpad.all_visited {
+            self.set_mark_unchecked(*v, Mark::NONE);
+        }
+        if result.is_ok() {
+            acyclic.extend(scratchpad.all_visited.iter().copied());
+        }
+        put_occurs_scratchpad(scratchpad);
+        result
+    }
+
     /// Returns the new recursion variable, which should be introduced to the environment as
     /// appropriate.
     #[must_use]
@@ -3586,6 +3586,26 @@ pub enum Mismatch {
     TypeNotInRange,
     DisjointLambdaSets,
     DoesNotImplementAbiity(Variable, Symbol),
+    /// A field demanded on one side of a record unification is optional on the other.
+    OptionalFieldConflict(Lowercase),
+    /// Where in the unifier a [Mismatch::TypeMismatch] was raised. Only recorded when asked for,
+    /// so that tests can check why a unification failed.
+    TypeMismatchSource(&'static str),
+    /// Unification nested more deeply than the limit set on the unification environment.
+    DepthExceeded,
+    /// Accompanies the mismatches raised while unifying the argument at this (zero-based)
+    /// position of a type application. Only the position in the outermost application is kept,
+    /// so for `Dict Str (List Str) ~ Dict Str (List I64)` it is 1, the position of `List Str`.
+    NthArgument(usize),
+    /// Accompanies the mismatches raised while unifying this record field. Only recorded when
+    /// unifying under `UnificationMode::COLLECT_ALL`.
+    InField(Lowercase),
+    /// Two function types take a different number of arguments. Following the solver's
+    /// convention, the right-hand-side of the unification is the expected type.
+    ArityMismatch {
+        expected: usize,
+        found: usize,
+    },
 }
 
 pub type DoesNotImplementAbility = Vec<(ErrorType, Symbol)>;
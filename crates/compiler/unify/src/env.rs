@@ -1,7 +1,10 @@
 #[cfg(debug_assertions)]
 use roc_checkmate::debug_checkmate;
-use roc_collections::VecSet;
-use roc_types::subs::{Descriptor, Subs, Variable};
+use roc_collections::{MutSet, VecSet};
+use roc_types::subs::{
+    Content, Descriptor, Subs, SubsSlice, SubsSnapshot, TagExt, UnionLambdas, UnionTags, Variable,
+};
+use roc_types::types::Uls;
 
 pub struct Env<'a> {
     subs: &'a mut Subs,
@@ -9,6 +12,16 @@ pub struct Env<'a> {
     cm: Option<&'a mut roc_checkmate::Collector>,
     seen_recursion: VecSet<(Variable, Variable)>,
     fixed_variables: VecSet<Variable>,
+    /// Roots known not to occur recursively, as of the last modification of `subs` through this
+    /// environment. Both sides of a unified tag payload are occurs-checked once it has been
+    /// unified, and by then they share a root, so the second check is answered from here.
+    acyclic: MutSet<Variable>,
+    /// How many unifications are currently nested.
+    depth: u32,
+    /// How deeply unifications may be nested before giving up. Unbounded if `None`.
+    depth_limit: Option<u32>,
+    /// Whether a nested unification has given up since the outermost one started.
+    depth_exceeded: bool,
 }
 
 impl std::ops::Deref for Env<'_> {
@@ -33,6 +46,10 @@ impl<'a> Env<'a> {
             cm,
             seen_recursion: Default::default(),
             fixed_variables: Default::default(),
+            acyclic: Default::default(),
+            depth: 0,
+            depth_limit: None,
+            depth_exceeded: false,
         }
     }
 
@@ -42,9 +59,45 @@ impl<'a> Env<'a> {
             subs,
             seen_recursion: Default::default(),
             fixed_variables: Default::default(),
+            acyclic: Default::default(),
+            depth: 0,
+            depth_limit: None,
+            depth_exceeded: false,
         }
     }
 
+    /// Bounds how deeply unifications through this environment may nest. Unifications nested
+    /// more deeply fail with [Mismatch::DepthExceeded] instead of overflowing the stack.
+    ///
+    /// [Mismatch::DepthExceeded]: roc_types::types::Mismatch::DepthExceeded
+    pub fn set_depth_limit(&mut self, limit: u32) {
+        self.depth_limit = Some(limit);
+    }
+
+    /// Starts a nested unification, returning `false` if that would exceed the depth limit.
+    pub(crate) fn enter_unification(&mut self) -> bool {
+        if matches!(self.depth_limit, Some(limit) if self.depth >= limit) {
+            self.depth_exceeded |= self.depth > 0;
+            return false;
+        }
+
+        if self.depth == 0 {
+            // `subs` may have been changed behind our back since the last unification.
+            self.invalidate_occurs_cache();
+        }
+
+        self.depth += 1;
+        true
+    }
+
+    /// Finishes a unification started with [Env::enter_unification]. Returns whether the depth
+    /// limit was exceeded somewhere within it, if this was the outermost unification.
+    pub(crate) fn exit_unification(&mut self) -> bool {
+        self.depth -= 1;
+
+        self.depth == 0 && std::mem::take(&mut self.depth_exceeded)
+    }
+
     pub(crate) fn add_recursion_pair(&mut self, var1: Variable, var2: Variable) {
         let pair = (
             self.subs.get_root_key_without_compacting(var1),
@@ -91,11 +144,71 @@ impl<'a> Env<'a> {
         self.fixed_variables.extend(vars);
     }
 
+    /// [Subs::occurs], skipping the check for variables already known to be acyclic.
+    pub(crate) fn occurs_cached(&mut self, var: Variable) -> Result<(), (Variable, Vec<Variable>)> {
+        let root = self.subs.get_root_key_without_compacting(var);
+        if self.acyclic.contains(&root) {
+            return Ok(());
+        }
+
+        self.subs.occurs_recording_acyclic(root, &mut self.acyclic)
+    }
+
+    /// Forgets all cached occurs checks. Must be called whenever `subs` is modified in a way that
+    /// could introduce a cycle, other than through the methods of this environment.
+    pub(crate) fn invalidate_occurs_cache(&mut self) {
+        self.acyclic.clear();
+    }
+
+    pub(crate) fn set(&mut self, var: Variable, desc: Descriptor) {
+        self.invalidate_occurs_cache();
+        self.subs.set(var, desc);
+    }
+
+    pub(crate) fn set_content(&mut self, var: Variable, content: Content) {
+        self.invalidate_occurs_cache();
+        self.subs.set_content(var, content);
+    }
+
+    pub(crate) fn rollback_to(&mut self, snapshot: SubsSnapshot) {
+        self.invalidate_occurs_cache();
+        self.subs.rollback_to(snapshot);
+    }
+
+    #[must_use]
+    pub(crate) fn mark_tag_union_recursive(
+        &mut self,
+        recursive: Variable,
+        tags: UnionTags,
+        ext: TagExt,
+    ) -> Variable {
+        self.invalidate_occurs_cache();
+        self.subs.mark_tag_union_recursive(recursive, tags, ext)
+    }
+
+    #[must_use]
+    pub(crate) fn mark_lambda_set_recursive(
+        &mut self,
+        recursive: Variable,
+        solved_lambdas: UnionLambdas,
+        unspecialized_lambdas: SubsSlice<Uls>,
+        ambient_function_var: Variable,
+    ) -> Variable {
+        self.invalidate_occurs_cache();
+        self.subs.mark_lambda_set_recursive(
+            recursive,
+            solved_lambdas,
+            unspecialized_lambdas,
+            ambient_function_var,
+        )
+    }
+
     #[cfg(debug_assertions)]
     pub(crate) fn union(&mut self, left: Variable, right: Variable, desc: Descriptor) {
         let left_root = self.subs.get_root_key_without_compacting(left);
         let right_root = self.subs.get_root_key_without_compacting(right);
 
+        self.invalidate_occurs_cache();
         self.subs.union(left, right, desc);
 
         debug_checkmate!(self.cm, cm => {
@@ -108,6 +221,7 @@ impl<'a> Env<'a> {
 
     #[cfg(not(debug_assertions))]
     pub(crate) fn union(&mut self, left: Variable, right: Variable, desc: Descriptor) {
+        self.invalidate_occurs_cache();
         self.subs.union(left, right, desc);
     }
 
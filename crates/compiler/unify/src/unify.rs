@@ -1,8 +1,9 @@
-use roc_collections::VecMap;
+use roc_checkmate::with_checkmate;
+use roc_collections::{MutSet, VecMap};
 use roc_debug_flags::{dbg_do, dbg_set};
 #[cfg(debug_assertions)]
 use roc_debug_flags::{
-    ROC_PRINT_MISMATCHES, ROC_PRINT_UNIFICATIONS, ROC_VERIFY_OCCURS_ONE_RECURSION,
+    ROC_PRINT_MISMATCHES, ROC_PRINT_UNIFICATIONS, ROC_TRACE_RANKS, ROC_VERIFY_OCCURS_ONE_RECURSION,
 };
 use roc_error_macros::{internal_error, todo_lambda_erasure};
 use roc_module::ident::{Lowercase, TagName};
@@ -11,9 +12,10 @@ use roc_solve_schema::UnificationMode;
 use roc_types::num::{FloatWidth, IntLitWidth, NumericRange};
 use roc_types::subs::Content::{self, *};
 use roc_types::subs::{
-    AliasVariables, Descriptor, ErrorTypeContext, FlatType, GetSubsSlice, LambdaSet, Mark,
-    OptVariable, RecordFields, Subs, SubsIndex, SubsSlice, TagExt, TupleElems, UlsOfVar,
-    UnionLabels, UnionLambdas, UnionTags, Variable, VariableSubsSlice,
+    storage_copy_var_to, AliasVariables, Descriptor, ErrorTypeContext, FlatType, GetSubsSlice,
+    LambdaSet, Mark, OptVariable, RecordFields, Subs, SubsIndex, SubsSlice, SubsSnapshot, TagExt,
+    TupleElems, UlsOfVar, UnionLabels, UnionLambdas, UnionTags, Variable, VariableMapCache,
+    VariableSubsSlice,
 };
 use roc_types::types::{
     AliasKind, DoesNotImplementAbility, ErrorType, Mismatch, Polarity, RecordField, Uls,
@@ -34,6 +36,7 @@ macro_rules! mismatch {
 
         Outcome {
             mismatches: vec![Mismatch::TypeMismatch],
+            mismatch_source: Some(concat!(file!(), ":", line!())),
             ..Outcome::default()
         }
     }};
@@ -51,6 +54,7 @@ macro_rules! mismatch {
 
         Outcome {
             mismatches: vec![Mismatch::TypeMismatch],
+            mismatch_source: Some($msg),
             ..Outcome::default()
         }
     }};
@@ -71,6 +75,7 @@ macro_rules! mismatch {
 
         Outcome {
             mismatches: vec![Mismatch::TypeMismatch],
+            mismatch_source: Some($msg),
             ..Outcome::default()
         }
     }};
@@ -94,6 +99,7 @@ macro_rules! mismatch {
 
         Outcome {
             mismatches,
+            mismatch_source: Some($msg),
             ..Outcome::default()
         }
     }}
@@ -173,6 +179,8 @@ pub enum Unified<M: MetaCollector = NoCollector> {
         vars: Pool,
         must_implement_ability: MustImplementConstraints,
         lambda_sets_to_specialize: UlsOfVar,
+        /// Whether unification changed any variable, rather than finding the types already equal.
+        changed: bool,
 
         /// The vast majority of the time the extra metadata is empty, so we make unification
         /// polymorphic over metadata collection to avoid unnecessary memory usage.
@@ -191,6 +199,7 @@ impl<M: MetaCollector> Unified<M> {
                 vars,
                 must_implement_ability,
                 lambda_sets_to_specialize,
+                changed: _,
                 extra_metadata,
             } => (
                 vars,
@@ -201,6 +210,14 @@ impl<M: MetaCollector> Unified<M> {
             _ => internal_error!("{}", err_msg),
         }
     }
+
+    /// The variables registered during this unification, each appearing once. An incremental
+    /// solver only needs to adjust the ranks of these.
+    pub fn fresh_vars(&self) -> &[Variable] {
+        match self {
+            Unified::Success { vars, .. } | Unified::Failure(vars, ..) => vars,
+        }
+    }
 }
 
 /// Type obligated to implement an ability.
@@ -261,6 +278,9 @@ pub struct Outcome<M: MetaCollector> {
     /// Whether any variable in the path has changed (been merged with new content).
     has_changed: bool,
     extra_metadata: M,
+    /// Where the first mismatch was raised. Only reported as a [Mismatch::TypeMismatchSource]
+    /// when unifying under [UnificationMode::RECORD_MISMATCH_SOURCE].
+    mismatch_source: Option<&'static str>,
 }
 
 impl<M: MetaCollector> Outcome<M> {
@@ -271,6 +291,7 @@ impl<M: MetaCollector> Outcome<M> {
             lambda_sets_to_specialize,
             has_changed,
             extra_metadata,
+            mismatch_source,
         } = other;
 
         self.mismatches.extend(mismatches);
@@ -279,6 +300,7 @@ impl<M: MetaCollector> Outcome<M> {
             .union(lambda_sets_to_specialize);
         self.has_changed = self.has_changed || has_changed;
         self.extra_metadata.union(extra_metadata);
+        self.mismatch_source = self.mismatch_source.or(mismatch_source);
     }
 }
 
@@ -328,6 +350,179 @@ pub fn unify_with_collector<M: MetaCollector>(
     unify_help(env, var1, var2, mode, observed_pol)
 }
 
+/// Checks whether `var1` and `var2` would unify, without changing either of them.
+///
+/// Unlike [unify], this never builds error types for a failed unification, so it is cheap to
+/// use where only a yes/no answer is needed.
+#[must_use]
+pub fn unifies(env: &mut Env, var1: Variable, var2: Variable, mode: UnificationMode) -> bool {
+    mode.debug_validate();
+
+    let snapshot = env.snapshot();
+
+    let mut vars = Vec::new();
+    let outcome: Outcome<NoCollector> = unify_pool(env, &mut vars, var1, var2, mode);
+
+    env.rollback_to(snapshot);
+
+    outcome.mismatches.is_empty()
+}
+
+/// Checks whether `var1` and `var2` are equal types, or can be made equal, leaving `subs`
+/// unchanged either way. This is [unifies] in [UnificationMode::EQ].
+#[must_use]
+pub fn types_equal(subs: &mut Subs, var1: Variable, var2: Variable) -> bool {
+    let mut env = with_checkmate!({
+        on => Env::new(subs, None),
+        off => Env::new(subs),
+    });
+
+    unifies(&mut env, var1, var2, UnificationMode::EQ)
+}
+
+/// The type that unifying `var1` and `var2` in [UnificationMode::EQ] would give them, or `None`
+/// if they do not unify. `subs` is left unchanged either way.
+///
+/// The unification is rolled back before this returns, so any variables it introduced, such as
+/// fresh record extensions, no longer exist. The type is therefore returned as an [ErrorType],
+/// which refers to no variables.
+#[must_use]
+pub fn preview_unify(subs: &mut Subs, var1: Variable, var2: Variable) -> Option<ErrorType> {
+    let mut env = with_checkmate!({
+        on => Env::new(subs, None),
+        off => Env::new(subs),
+    });
+
+    let snapshot = env.snapshot();
+
+    let mut vars = Vec::new();
+    let outcome: Outcome<NoCollector> =
+        unify_pool(&mut env, &mut vars, var1, var2, UnificationMode::EQ);
+
+    let merged = outcome
+        .mismatches
+        .is_empty()
+        .then(|| env.var_to_error_type(var1, Polarity::OF_VALUE));
+
+    env.rollback_to(snapshot);
+
+    merged
+}
+
+/// Unifies fresh deep copies of `var1` and `var2`, returning the copies along with the result.
+/// `var1`, `var2` and everything they refer to are left untouched, so this can be repeated for
+/// speculative checks without snapshots. Variables shared by `var1` and `var2` are shared by the
+/// copies too.
+#[must_use]
+pub fn unify_copies(
+    subs: &mut Subs,
+    var1: Variable,
+    var2: Variable,
+    mode: UnificationMode,
+) -> (Variable, Variable, Unified) {
+    // `storage_copy_var_to` copies between two different `Subs`, so go through a scratch one
+    let mut scratch = Subs::new();
+
+    let mut to_scratch = VariableMapCache::default();
+    let scratch1 = storage_copy_var_to(&mut to_scratch, subs, &mut scratch, var1);
+    let scratch2 = storage_copy_var_to(&mut to_scratch, subs, &mut scratch, var2);
+
+    let mut from_scratch = VariableMapCache::default();
+    let copy1 = storage_copy_var_to(&mut from_scratch, &scratch, subs, scratch1);
+    let copy2 = storage_copy_var_to(&mut from_scratch, &scratch, subs, scratch2);
+
+    let mut env = with_checkmate!({
+        on => Env::new(subs, None),
+        off => Env::new(subs),
+    });
+
+    let unified = unify(&mut env, copy1, copy2, mode, Polarity::OF_VALUE);
+
+    (copy1, copy2, unified)
+}
+
+/// Unifies variables tentatively, so that a caller can try several unifications and keep only
+/// the ones it wants.
+///
+/// A failed [Unifier::try_unify] leaves `subs` as it was. Successful ones are kept until the
+/// next [Unifier::commit], which makes them permanent, or [Unifier::rollback], which undoes them.
+/// Anything not committed when the unifier is dropped is undone.
+pub struct Unifier<'a> {
+    env: Env<'a>,
+    uncommitted: Option<SubsSnapshot>,
+    /// The variables introduced by the uncommitted unifications.
+    vars: Pool,
+}
+
+impl<'a> Unifier<'a> {
+    pub fn new(subs: &'a mut Subs) -> Self {
+        let env = with_checkmate!({
+            on => Env::new(subs, None),
+            off => Env::new(subs),
+        });
+
+        Self {
+            env,
+            uncommitted: None,
+            vars: Vec::new(),
+        }
+    }
+
+    /// Unifies `var1` and `var2`, returning whether that succeeded. On failure, nothing changes.
+    #[must_use]
+    pub fn try_unify(&mut self, var1: Variable, var2: Variable, mode: UnificationMode) -> bool {
+        mode.debug_validate();
+
+        let snapshot = self.env.snapshot();
+
+        let vars_len = self.vars.len();
+        let outcome: Outcome<NoCollector> =
+            unify_pool(&mut self.env, &mut self.vars, var1, var2, mode);
+
+        if !outcome.mismatches.is_empty() {
+            self.env.rollback_to(snapshot);
+            self.vars.truncate(vars_len);
+            return false;
+        }
+
+        match self.uncommitted {
+            None => self.uncommitted = Some(snapshot),
+            Some(_) => self.env.commit_snapshot(snapshot),
+        }
+
+        true
+    }
+
+    /// Keeps every successful unification made since the last commit or rollback. Returns the
+    /// variables they introduced, which belong in the caller's pools like the `vars` of
+    /// [Unified::Success].
+    pub fn commit(&mut self) -> Vec<Variable> {
+        if let Some(snapshot) = self.uncommitted.take() {
+            self.env.commit_snapshot(snapshot);
+        }
+
+        let mut vars = std::mem::take(&mut self.vars);
+        dedup_pool(&mut vars);
+
+        vars
+    }
+
+    /// Undoes every successful unification made since the last commit or rollback.
+    pub fn rollback(&mut self) {
+        if let Some(snapshot) = self.uncommitted.take() {
+            self.env.rollback_to(snapshot);
+        }
+
+        self.vars.clear();
+    }
+}
+
+impl Drop for Unifier<'_> {
+    fn drop(&mut self) {
+        self.rollback();
+    }
+}
+
 #[inline(always)]
 #[must_use]
 fn unify_help<M: MetaCollector>(
@@ -337,20 +532,26 @@ fn unify_help<M: MetaCollector>(
     mode: UnificationMode,
     observed_pol: Polarity,
 ) -> Unified<M> {
+    mode.debug_validate();
+
     let mut vars = Vec::new();
     let Outcome {
         mismatches,
         must_implement_ability,
         lambda_sets_to_specialize,
         extra_metadata,
-        has_changed: _,
+        has_changed,
+        mismatch_source: _,
     } = unify_pool(env, &mut vars, var1, var2, mode);
 
+    dedup_pool(&mut vars);
+
     if mismatches.is_empty() {
         Unified::Success {
             vars,
             must_implement_ability,
             lambda_sets_to_specialize,
+            changed: has_changed,
             extra_metadata,
         }
     } else {
@@ -381,6 +582,12 @@ fn unify_help<M: MetaCollector>(
     }
 }
 
+/// Removes repeated variables from a pool, keeping the first occurrence of each.
+fn dedup_pool(pool: &mut Pool) {
+    let mut seen = MutSet::default();
+    pool.retain(|var| seen.insert(*var));
+}
+
 #[inline(always)]
 #[must_use]
 pub fn unify_pool<M: MetaCollector>(
@@ -401,7 +608,26 @@ pub fn unify_pool<M: MetaCollector>(
             mode,
         };
 
-        unify_context(env, pool, ctx)
+        let mut outcome = unify_context(env, pool, ctx);
+
+        if mode.contains(UnificationMode::RECORD_MISMATCH_SOURCE) {
+            // Only the innermost failure says where the mismatch was raised; the levels that
+            // enclose it fail because of it.
+            let already_recorded = outcome
+                .mismatches
+                .iter()
+                .any(|m| matches!(m, Mismatch::TypeMismatchSource(_)));
+
+            if let Some(source) = outcome.mismatch_source.take() {
+                if !already_recorded {
+                    outcome
+                        .mismatches
+                        .push(Mismatch::TypeMismatchSource(source));
+                }
+            }
+        }
+
+        outcome
     }
 }
 
@@ -473,6 +699,13 @@ fn debug_print_unified_types<M: MetaCollector>(
 
 #[must_use]
 fn unify_context<M: MetaCollector>(env: &mut Env, pool: &mut Pool, ctx: Context) -> Outcome<M> {
+    if !env.enter_unification() {
+        return Outcome {
+            mismatches: vec![Mismatch::DepthExceeded],
+            ..Outcome::default()
+        };
+    }
+
     #[cfg(debug_assertions)]
     debug_print_unified_types::<M>(env, &ctx, None);
 
@@ -528,6 +761,15 @@ fn unify_context<M: MetaCollector>(env: &mut Env, pool: &mut Pool, ctx: Context)
     #[cfg(debug_assertions)]
     debug_print_unified_types(env, &ctx, Some(&result));
 
+    if env.exit_unification()
+        && !result.mismatches.is_empty()
+        && !result.mismatches.contains(&Mismatch::DepthExceeded)
+    {
+        // nested mismatches are not always passed up as they are, so make sure the reason
+        // unification gave up is not lost
+        result.mismatches.push(Mismatch::DepthExceeded);
+    }
+
     result
 }
 
@@ -538,6 +780,7 @@ fn not_in_range_mismatch<M: MetaCollector>() -> Outcome<M> {
         lambda_sets_to_specialize: Default::default(),
         has_changed: false,
         extra_metadata: Default::default(),
+        mismatch_source: None,
     }
 }
 
@@ -726,6 +969,23 @@ fn unify_two_aliases<M: MetaCollector>(
     other_real_var: Variable,
 ) -> Outcome<M> {
     if args.len() == other_args.len() {
+        // Instantiations of the same alias with the same arguments are common. Their arguments
+        // need no unifying, and no merged arguments need to be written; only the real vars are
+        // unified, for the reason given below.
+        let same_arguments = (args.all_variables().into_iter())
+            .zip(other_args.all_variables())
+            .all(|(l, r)| env.equivalent_without_compacting(env[l], env[r]));
+
+        if same_arguments {
+            let mut outcome = unify_pool::<M>(env, pool, real_var, other_real_var, ctx.mode);
+            let _ = outcome.mismatches.drain(..);
+
+            let merged_real_var = choose_merged_var(env, real_var, other_real_var);
+            outcome.union(merge(env, ctx, Alias(symbol, args, merged_real_var, kind)));
+
+            return outcome;
+        }
+
         let mut outcome = Outcome::default();
 
         let args_it = args
@@ -840,6 +1100,7 @@ fn unify_two_aliases<M: MetaCollector>(
 }
 
 fn fix_fixpoint<M: MetaCollector>(env: &mut Env, ctx: &Context) -> Outcome<M> {
+    env.invalidate_occurs_cache();
     let fixed_variables = crate::fix::fix_fixpoint(env, ctx.first, ctx.second);
     env.extend_fixed_variables(fixed_variables);
     Default::default()
@@ -1904,6 +2165,17 @@ fn unify_record<M: MetaCollector>(
 ) -> Outcome<M> {
     let (separate, ext1, ext2) = separate_record_fields(env, fields1, ext1, fields2, ext2);
 
+    if ctx.mode.is_closed_record() && !separate.only_in_2.is_empty() {
+        return mismatch!(
+            "Record on the left is closed, but the right has extra fields {:?}",
+            separate
+                .only_in_2
+                .iter()
+                .map(|(name, _)| name)
+                .collect::<Vec<_>>()
+        );
+    }
+
     let shared_fields = separate.in_both;
 
     if separate.only_in_1.is_empty() {
@@ -2120,8 +2392,15 @@ fn unify_shared_fields<M: MetaCollector>(
     let num_shared_fields = shared_fields.len();
 
     let mut whole_outcome = Outcome::default();
+    let mut optional_conflicts = Vec::new();
+    let mut field_mismatches = Vec::new();
 
     for (name, (actual, expected)) in shared_fields {
+        let snapshot = ctx
+            .mode
+            .is_collect_all()
+            .then(|| (env.snapshot(), pool.len()));
+
         let local_outcome = unify_pool(
             env,
             pool,
@@ -2130,6 +2409,18 @@ fn unify_shared_fields<M: MetaCollector>(
             ctx.mode,
         );
 
+        if let Some((snapshot, pool_len)) = snapshot {
+            if local_outcome.mismatches.is_empty() {
+                env.commit_snapshot(snapshot);
+            } else {
+                env.rollback_to(snapshot);
+                pool.truncate(pool_len);
+                field_mismatches.extend(local_outcome.mismatches);
+                field_mismatches.push(Mismatch::InField(name));
+                continue;
+            }
+        }
+
         if local_outcome.mismatches.is_empty() {
             use RecordField::*;
 
@@ -2145,6 +2436,7 @@ fn unify_shared_fields<M: MetaCollector>(
             let actual = match (actual, expected) {
                 (Demanded(_), Optional(_)) | (Optional(_), Demanded(_)) => {
                     // this is an error, but we continue to give better error messages
+                    optional_conflicts.push(Mismatch::OptionalFieldConflict(name));
                     continue;
                 }
 
@@ -2182,6 +2474,8 @@ fn unify_shared_fields<M: MetaCollector>(
 
             matching_fields.push((name, actual));
             whole_outcome.union(local_outcome);
+        } else {
+            field_mismatches.extend(mismatch_sources(local_outcome.mismatches));
         }
     }
 
@@ -2234,7 +2528,10 @@ fn unify_shared_fields<M: MetaCollector>(
         whole_outcome.union(merge_outcome);
         whole_outcome
     } else {
-        mismatch!("in unify_shared_fields")
+        let mut outcome = mismatch!("in unify_shared_fields");
+        outcome.mismatches.extend(optional_conflicts);
+        outcome.mismatches.extend(field_mismatches);
+        outcome
     }
 }
 
@@ -2258,6 +2555,7 @@ fn unify_shared_tuple_elems<M: MetaCollector>(
     let num_shared_elems = shared_elems.len();
 
     let mut whole_outcome = Outcome::default();
+    let mut elem_sources = Vec::new();
 
     for (name, (actual, expected)) in shared_elems {
         let local_outcome = unify_pool(env, pool, actual, expected, ctx.mode);
@@ -2267,6 +2565,8 @@ fn unify_shared_tuple_elems<M: MetaCollector>(
 
             matching_elems.push((name, actual));
             whole_outcome.union(local_outcome);
+        } else {
+            elem_sources.extend(mismatch_sources(local_outcome.mismatches));
         }
     }
 
@@ -2317,10 +2617,20 @@ fn unify_shared_tuple_elems<M: MetaCollector>(
         whole_outcome.union(merge_outcome);
         whole_outcome
     } else {
-        mismatch!("in unify_shared_tuple_elems")
+        let mut outcome = mismatch!("in unify_shared_tuple_elems");
+        outcome.mismatches.extend(elem_sources);
+        outcome
     }
 }
 
+/// The [Mismatch::TypeMismatchSource]s of a failed sub-unification whose other mismatches are
+/// about to be dropped. Keeping them lets the reported source stay the innermost one.
+fn mismatch_sources(mismatches: Vec<Mismatch>) -> impl Iterator<Item = Mismatch> {
+    mismatches
+        .into_iter()
+        .filter(|mismatch| matches!(mismatch, Mismatch::TypeMismatchSource(_)))
+}
+
 fn separate_record_fields(
     subs: &Subs,
     fields1: RecordFields,
@@ -2665,6 +2975,14 @@ fn unify_tag_unions<M: MetaCollector>(
         (ctx.mode.is_present(), env.get(ext1.var()).content)
     {
         if !separate.only_in_2.is_empty() {
+            if ctx.mode.is_no_open() {
+                return mismatch!(
+                    "Tag union {:?} may not grow new tags under {:?}",
+                    ctx.first,
+                    ctx.mode
+                );
+            }
+
             // Create a new extension variable that we'll fill in with the
             // contents of the tag union from our presence contraint.
             //
@@ -2889,8 +3207,12 @@ enum OtherTags2 {
 
 /// Promotes a non-recursive tag union or lambda set to its recursive variant, if it is found to be
 /// recursive.
+///
+/// The occurs check is cached in the [Env] until `subs` is next modified. Callers check both sides
+/// of a payload right after unifying them, so the second side, which now shares the first's root,
+/// costs a lookup rather than another walk of the whole payload.
 fn maybe_mark_union_recursive(env: &mut Env, pool: &mut Pool, union_var: Variable) {
-    'outer: while let Err((_, chain)) = env.occurs(union_var) {
+    'outer: while let Err((_, chain)) = env.occurs_cached(union_var) {
         // walk the chain till we find a tag union or lambda set, starting from the variable that
         // occurred recursively, which is always at the end of the chain.
         for &v in chain.iter().rev() {
@@ -3066,7 +3388,13 @@ fn unify_shared_tags<M: MetaCollector>(
 
             let mut outcome = Outcome::<M>::default();
 
-            outcome.union(unify_pool(env, pool, actual, expected, ctx.mode));
+            outcome.union(unify_pool(
+                env,
+                pool,
+                actual,
+                expected,
+                ctx.mode.for_payloads(),
+            ));
 
             if outcome.mismatches.is_empty() {
                 let merged_var = choose_merged_var(env, actual, expected);
@@ -3212,6 +3540,29 @@ fn unify_flat_type<M: MetaCollector>(
             unify_pool(env, pool, ctx.first, ext.var(), ctx.mode)
         }
 
+        (TagUnion(tags1, ext1), TagUnion(tags2, ext2))
+            if is_bool_enum(env, *tags1, *ext1) && is_bool_enum(env, *tags2, *ext2) =>
+        {
+            // Fast path: `[False, True]` shows up everywhere, and there is nothing to separate
+            // or extend when both sides are exactly that closed union.
+            //
+            // Like the general path, still unify the two empty extensions under equality, so they
+            // are not later copied (or exported) as two distinct variables.
+            let mut outcome = if ctx.mode.is_eq() {
+                unify_pool(env, pool, ext1.var(), ext2.var(), ctx.mode)
+            } else {
+                Outcome::default()
+            };
+
+            outcome.union(merge(
+                env,
+                ctx,
+                Structure(TagUnion(*tags1, merge_tag_exts(*ext1, *ext2))),
+            ));
+
+            outcome
+        }
+
         (TagUnion(tags1, ext1), TagUnion(tags2, ext2)) => {
             unify_tag_unions(env, pool, ctx, *tags1, *ext1, *tags2, *ext2)
         }
@@ -3236,11 +3587,27 @@ fn unify_flat_type<M: MetaCollector>(
             let mut outcome = unify_tag_unions(env, pool, ctx, *tags1, *ext1, *tags2, *ext2);
             outcome.union(unify_pool(env, pool, *rec1, *rec2, ctx.mode));
 
+            if outcome.mismatches.is_empty() {
+                // Whichever recursion var was kept must still head the merged union. If the two
+                // recursion vars described equivalent unions through different structures, the
+                // merged union may have picked up a cycle that is not yet behind a recursion var.
+                let still_recursive = matches!(
+                    env.get_content_without_compacting(ctx.first),
+                    Structure(RecursiveTagUnion(rec, _, _)) if is_recursion_var(env, *rec)
+                );
+                debug_assert!(still_recursive, "{:?}", env.dbg(ctx.first));
+
+                if !still_recursive {
+                    // Only in release builds: recover by marking whatever cycle is left.
+                    maybe_mark_union_recursive(env, pool, ctx.first);
+                }
+            }
+
             outcome
         }
 
         (Apply(l_symbol, l_args), Apply(r_symbol, r_args)) if l_symbol == r_symbol => {
-            let mut outcome = unify_zip_slices(env, pool, *l_args, *r_args, ctx.mode);
+            let mut outcome = unify_zip_slices(env, pool, *l_args, *r_args, ctx.mode, true);
 
             if outcome.mismatches.is_empty() {
                 let chosen_args = SubsSlice::reserve_into_subs(env, l_args.len());
@@ -3262,7 +3629,7 @@ fn unify_flat_type<M: MetaCollector>(
         (Func(l_args, l_closure, l_ret), Func(r_args, r_closure, r_ret))
             if l_args.len() == r_args.len() =>
         {
-            let arg_outcome = unify_zip_slices(env, pool, *l_args, *r_args, ctx.mode);
+            let arg_outcome = unify_zip_slices(env, pool, *l_args, *r_args, ctx.mode, false);
             let ret_outcome = unify_pool(env, pool, *l_ret, *r_ret, ctx.mode);
             let closure_outcome = unify_pool(env, pool, *l_closure, *r_closure, ctx.mode);
 
@@ -3283,6 +3650,19 @@ fn unify_flat_type<M: MetaCollector>(
 
             outcome
         }
+        (Func(l_args, _, _), Func(r_args, _, _)) => {
+            let mut outcome = mismatch!(
+                "Functions take different numbers of arguments: {} and {}",
+                l_args.len(),
+                r_args.len()
+            );
+            outcome.mismatches.push(Mismatch::ArityMismatch {
+                expected: r_args.len(),
+                found: l_args.len(),
+            });
+
+            outcome
+        }
         (FunctionOrTagUnion(tag_names, tag_symbols, ext), Func(args, closure, ret)) => {
             unify_function_or_tag_union_and_func(
                 env,
@@ -3381,6 +3761,22 @@ fn unify_flat_type<M: MetaCollector>(
     }
 }
 
+/// Is this exactly the closed, payload-free union `[False, True]` that backs `Bool`?
+#[inline(always)]
+fn is_bool_enum(subs: &Subs, tags: UnionTags, ext: TagExt) -> bool {
+    tags.len() == 2
+        && matches!(
+            subs.get_content_without_compacting(ext.var()),
+            Structure(FlatType::EmptyTagUnion)
+        )
+        && tags.iter_from_subs(subs).zip(["False", "True"]).all(
+            |((tag_name, payload), expected)| payload.is_empty() && tag_name.0.as_str() == expected,
+        )
+}
+
+/// Unifies the variables of `left` and `right` pairwise. When `record_positions` is set, the
+/// mismatches of each pair are tagged with its position, replacing the positions recorded for any
+/// type applications nested in it.
 #[must_use]
 fn unify_zip_slices<M: MetaCollector>(
     env: &mut Env,
@@ -3388,16 +3784,35 @@ fn unify_zip_slices<M: MetaCollector>(
     left: SubsSlice<Variable>,
     right: SubsSlice<Variable>,
     mode: UnificationMode,
+    record_positions: bool,
 ) -> Outcome<M> {
     let mut outcome = Outcome::default();
 
     let it = left.into_iter().zip(right);
 
-    for (l_index, r_index) in it {
+    for (position, (l_index, r_index)) in it.enumerate() {
         let l_var = env[l_index];
         let r_var = env[r_index];
 
-        outcome.union(unify_pool(env, pool, l_var, r_var, mode));
+        let snapshot = mode.is_collect_all().then(|| (env.snapshot(), pool.len()));
+
+        let mut arg_outcome: Outcome<M> = unify_pool(env, pool, l_var, r_var, mode);
+        if record_positions && !arg_outcome.mismatches.is_empty() {
+            let mismatches = &mut arg_outcome.mismatches;
+            mismatches.retain(|mismatch| !matches!(mismatch, Mismatch::NthArgument(_)));
+            mismatches.push(Mismatch::NthArgument(position));
+        }
+
+        if let Some((snapshot, pool_len)) = snapshot {
+            if arg_outcome.mismatches.is_empty() {
+                env.commit_snapshot(snapshot);
+            } else {
+                env.rollback_to(snapshot);
+                pool.truncate(pool_len);
+            }
+        }
+
+        outcome.union(arg_outcome);
     }
 
     outcome
@@ -3538,7 +3953,6 @@ fn unify_flex<M: MetaCollector>(
         | RangedNumber(..)
         | LambdaSet(..)
         | ErasedLambda => {
-            // TODO special-case boolean here
             // In all other cases, if left is flex, defer to right.
             merge(env, ctx, *other)
         }
@@ -3821,12 +4235,40 @@ pub fn merge<M: MetaCollector>(env: &mut Env, ctx: &Context, content: Content) -
         copy: OptVariable::NONE,
     };
 
+    dbg_do!(ROC_TRACE_RANKS, {
+        eprintln!("{}", trace_rank(env, ctx, &desc));
+    });
+
+    // tests record traces on their own thread rather than setting `ROC_TRACE_RANKS`
+    #[cfg(all(test, debug_assertions))]
+    test::RANK_TRACES.with(|traces| {
+        if let Some(traces) = traces.borrow_mut().as_mut() {
+            traces.push(trace_rank(env, ctx, &desc));
+        }
+    });
+
     env.union(ctx.first, ctx.second, desc);
 
     outcome.has_changed = true;
     outcome
 }
 
+/// Describes the ranks merged by [merge], and the rank it chose.
+#[cfg(debug_assertions)]
+fn trace_rank(env: &Env, ctx: &Context, merged: &Descriptor) -> String {
+    use roc_types::subs::SubsFmtContent;
+
+    format!(
+        "merge {:?} ~ {:?}: rank {:?} ~ {:?} => {:?}, content {:?}",
+        ctx.first,
+        ctx.second,
+        ctx.first_desc.rank,
+        ctx.second_desc.rank,
+        merged.rank,
+        SubsFmtContent(&merged.content, env),
+    )
+}
+
 fn register(env: &mut Env, desc: Descriptor, pool: &mut Pool) -> Variable {
     let var = env.fresh(desc);
 
@@ -3986,3 +4428,1334 @@ fn unify_two_function_or_tag_unions<M: MetaCollector>(
     outcome.union(merge_outcome);
     outcome
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use roc_types::subs::Rank;
+    use roc_types::types::TypeExt;
+
+    #[cfg(debug_assertions)]
+    thread_local! {
+        /// The rank traces emitted on this thread, while they are being recorded.
+        pub(super) static RANK_TRACES: std::cell::RefCell<Option<Vec<String>>> = Default::default();
+    }
+
+    fn new_var(subs: &mut Subs, content: Content) -> Variable {
+        subs.fresh(Descriptor {
+            content,
+            rank: Rank::toplevel(),
+            mark: Mark::NONE,
+            copy: OptVariable::NONE,
+        })
+    }
+
+    fn unify_vars(
+        subs: &mut Subs,
+        var1: Variable,
+        var2: Variable,
+        mode: UnificationMode,
+    ) -> Unified {
+        unify(
+            &mut with_checkmate!({
+                on => Env::new(subs, None),
+                off => Env::new(subs),
+            }),
+            var1,
+            var2,
+            mode,
+            Polarity::OF_VALUE,
+        )
+    }
+
+    fn record_of_str(subs: &mut Subs, field: &str) -> Variable {
+        record_of(subs, field, Variable::STR)
+    }
+
+    /// `{ field : var }`
+    fn record_of(subs: &mut Subs, field: &str, var: Variable) -> Variable {
+        let fields = RecordFields::insert_into_subs(
+            subs,
+            [(Lowercase::from(field), RecordField::Required(var))],
+        );
+        new_var(
+            subs,
+            Structure(FlatType::Record(fields, Variable::EMPTY_RECORD)),
+        )
+    }
+
+    fn flex_able_var(subs: &mut Subs, abilities: &[Symbol]) -> Variable {
+        let abilities = SubsSlice::extend_new(&mut subs.symbol_names, abilities.iter().copied());
+        new_var(subs, FlexAbleVar(None, abilities))
+    }
+
+    fn bool_enum(subs: &mut Subs, ext: Variable) -> Variable {
+        let tags = UnionTags::insert_into_subs(
+            subs,
+            [(TagName("False".into()), []), (TagName("True".into()), [])],
+        );
+        new_var(subs, Structure(FlatType::TagUnion(tags, TagExt::Any(ext))))
+    }
+
+    fn tag_union(subs: &mut Subs, tags: &[&str]) -> Variable {
+        let tags =
+            UnionTags::insert_into_subs(subs, tags.iter().map(|tag| (TagName((*tag).into()), [])));
+        new_var(
+            subs,
+            Structure(FlatType::TagUnion(
+                tags,
+                TagExt::Any(Variable::EMPTY_TAG_UNION),
+            )),
+        )
+    }
+
+    /// `[A (nested), B]`, `depth` levels deep.
+    fn nested_union(subs: &mut Subs, depth: usize) -> Variable {
+        let mut var = tag_union(subs, &["B"]);
+        for _ in 0..depth {
+            let tags = UnionTags::insert_into_subs(
+                subs,
+                [
+                    (TagName("A".into()), vec![var]),
+                    (TagName("B".into()), vec![]),
+                ],
+            );
+            var = new_var(
+                subs,
+                Structure(FlatType::TagUnion(
+                    tags,
+                    TagExt::Any(Variable::EMPTY_TAG_UNION),
+                )),
+            );
+        }
+        var
+    }
+
+    /// `Set elem`, a builtin opaque with a single type parameter.
+    fn set_of(subs: &mut Subs, elem: Variable) -> Variable {
+        let real_var = new_var(subs, FlexVar(None));
+        let args = AliasVariables::insert_into_subs(subs, [elem], [], []);
+        new_var(
+            subs,
+            Alias(Symbol::SET_SET, args, real_var, AliasKind::Opaque),
+        )
+    }
+
+    #[test]
+    fn structural_record_satisfies_flex_able_bound() {
+        let mut subs = Subs::new();
+
+        let record = record_of_str(&mut subs, "x");
+        let able = flex_able_var(&mut subs, &[Symbol::BOOL_EQ]);
+
+        let (_, must_implement, _, _) = unify_vars(&mut subs, record, able, UnificationMode::EQ)
+            .expect_success("record should unify with a flex-able var");
+
+        assert_eq!(
+            must_implement.get_unique(),
+            [MustImplementAbility {
+                typ: Obligated::Adhoc(record),
+                ability: Symbol::BOOL_EQ,
+            }]
+        );
+        assert!(matches!(
+            subs.get_content_without_compacting(able),
+            Structure(FlatType::Record(..))
+        ));
+    }
+
+    #[test]
+    fn flex_able_bound_obligates_structural_record() {
+        let mut subs = Subs::new();
+
+        let able = flex_able_var(&mut subs, &[Symbol::BOOL_EQ, Symbol::HASH_HASH_ABILITY]);
+        let record = record_of_str(&mut subs, "x");
+
+        let (_, must_implement, _, _) = unify_vars(&mut subs, able, record, UnificationMode::EQ)
+            .expect_success("flex-able var should unify with a record");
+
+        let mut expected = vec![
+            MustImplementAbility {
+                typ: Obligated::Adhoc(record),
+                ability: Symbol::BOOL_EQ,
+            },
+            MustImplementAbility {
+                typ: Obligated::Adhoc(record),
+                ability: Symbol::HASH_HASH_ABILITY,
+            },
+        ];
+        expected.sort();
+
+        assert_eq!(must_implement.get_unique(), expected);
+    }
+
+    #[test]
+    fn many_bool_enums_unify_to_one_bool_enum() {
+        let mut subs = Subs::new();
+
+        let bools: Vec<_> = (0..1_000)
+            .map(|_| bool_enum(&mut subs, Variable::EMPTY_TAG_UNION))
+            .collect();
+
+        for pair in bools.windows(2) {
+            let _ = unify_vars(&mut subs, pair[0], pair[1], UnificationMode::EQ)
+                .expect_success("[False, True] should unify with itself");
+        }
+
+        let first = bools[0];
+        assert!(bools
+            .iter()
+            .all(|&var| subs.equivalent_without_compacting(first, var)));
+
+        match subs.get_content_without_compacting(first) {
+            Structure(FlatType::TagUnion(tags, ext)) => {
+                assert!(is_bool_enum(&subs, *tags, *ext));
+            }
+            other => panic!("expected a tag union, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn bool_enum_fast_path_unifies_extensions() {
+        let mut subs = Subs::new();
+
+        let ext1 = new_var(&mut subs, Structure(FlatType::EmptyTagUnion));
+        let ext2 = new_var(&mut subs, Structure(FlatType::EmptyTagUnion));
+        let bool1 = bool_enum(&mut subs, ext1);
+        let bool2 = bool_enum(&mut subs, ext2);
+
+        let _ = unify_vars(&mut subs, bool1, bool2, UnificationMode::EQ)
+            .expect_success("[False, True] should unify with itself");
+
+        assert!(subs.equivalent_without_compacting(ext1, ext2));
+    }
+
+    #[test]
+    fn open_bool_enum_takes_general_path() {
+        let mut subs = Subs::new();
+
+        let ext = new_var(&mut subs, FlexVar(None));
+        let open = bool_enum(&mut subs, ext);
+        let closed = bool_enum(&mut subs, Variable::EMPTY_TAG_UNION);
+
+        let _ = unify_vars(&mut subs, open, closed, UnificationMode::EQ)
+            .expect_success("[False, True]a should unify with [False, True]");
+
+        // The open extension is only closed by the general tag union path.
+        assert!(matches!(
+            subs.get_content_without_compacting(ext),
+            Structure(FlatType::EmptyTagUnion)
+        ));
+        match subs.get_content_without_compacting(open) {
+            Structure(FlatType::TagUnion(tags, ext)) => {
+                assert!(is_bool_enum(&subs, *tags, *ext));
+            }
+            other => panic!("expected a tag union, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parameterized_opaque_satisfies_flex_able_bound() {
+        let mut subs = Subs::new();
+
+        let elem = new_var(&mut subs, FlexVar(None));
+        let set = set_of(&mut subs, elem);
+        let able = flex_able_var(&mut subs, &[Symbol::BOOL_EQ]);
+
+        let (_, must_implement, _, _) = unify_vars(&mut subs, set, able, UnificationMode::EQ)
+            .expect_success("Set a should unify with a flex-able var");
+
+        assert_eq!(
+            must_implement.get_unique(),
+            [MustImplementAbility {
+                typ: Obligated::Opaque(Symbol::SET_SET),
+                ability: Symbol::BOOL_EQ,
+            }]
+        );
+        assert!(matches!(
+            subs.get_content_without_compacting(able),
+            Alias(Symbol::SET_SET, args, _, AliasKind::Opaque) if args.len() == 1
+        ));
+    }
+
+    #[test]
+    fn unifying_opaques_narrows_their_real_types() {
+        let mut subs = Subs::new();
+        let elem = new_var(&mut subs, FlexVar(None));
+
+        // `Set a` and `Set Str`, with `{ x : a }` and `{ x : Str }` as their real types
+        let mut set_with_real_field = |elem| {
+            let fields = RecordFields::insert_into_subs(
+                &mut subs,
+                [(Lowercase::from("x"), RecordField::Required(elem))],
+            );
+            let real_var = new_var(
+                &mut subs,
+                Structure(FlatType::Record(fields, Variable::EMPTY_RECORD)),
+            );
+            let args = AliasVariables::insert_into_subs(&mut subs, [elem], [], []);
+            let set = new_var(
+                &mut subs,
+                Alias(Symbol::SET_SET, args, real_var, AliasKind::Opaque),
+            );
+            (set, real_var)
+        };
+        let (set_a, real_a) = set_with_real_field(elem);
+        let (set_str, real_str) = set_with_real_field(Variable::STR);
+
+        let _ = unify_vars(&mut subs, set_a, set_str, UnificationMode::EQ)
+            .expect_success("Set a should unify with Set Str");
+
+        assert!(subs.equivalent_without_compacting(elem, Variable::STR));
+        assert!(subs.equivalent_without_compacting(real_a, real_str));
+    }
+
+    #[test]
+    fn identical_opaque_instantiations_merge_directly() {
+        let mut subs = Subs::new();
+
+        // two instantiations of `Set elem`, each with its own real var `{ list : elem }`
+        let elem = new_var(&mut subs, FlexVar(None));
+        let mut set_of_elem = || {
+            let real_var = record_of(&mut subs, "list", elem);
+            let args = AliasVariables::insert_into_subs(&mut subs, [elem], [], []);
+            let set = new_var(
+                &mut subs,
+                Alias(Symbol::SET_SET, args, real_var, AliasKind::Opaque),
+            );
+            (set, args, real_var)
+        };
+        let (set1, args1, real_var1) = set_of_elem();
+        let (set2, _, real_var2) = set_of_elem();
+
+        let len_before = subs.len();
+        let vars = match unify_vars(&mut subs, set1, set2, UnificationMode::EQ) {
+            Unified::Success { vars, .. } => vars,
+            _ => panic!("identical instantiations of Set should unify"),
+        };
+
+        assert!(subs.equivalent_without_compacting(set1, set2));
+        assert!(subs.equivalent_without_compacting(real_var1, real_var2));
+        assert!(vars.is_empty());
+        assert_eq!(subs.len(), len_before);
+
+        // the arguments were not walked and written out again
+        match subs.get_content_without_compacting(set1) {
+            Alias(_, args, _, _) => assert_eq!(args.variables_start, args1.variables_start),
+            other => panic!("expected an opaque, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn flex_able_bound_obligates_parameterized_opaque() {
+        let mut subs = Subs::new();
+
+        let able = flex_able_var(&mut subs, &[Symbol::HASH_HASH_ABILITY]);
+        let set = set_of(&mut subs, Variable::STR);
+
+        let (_, must_implement, _, _) = unify_vars(&mut subs, able, set, UnificationMode::EQ)
+            .expect_success("flex-able var should unify with Set Str");
+
+        assert_eq!(
+            must_implement.get_unique(),
+            [MustImplementAbility {
+                typ: Obligated::Opaque(Symbol::SET_SET),
+                ability: Symbol::HASH_HASH_ABILITY,
+            }]
+        );
+    }
+
+    fn unifies_vars(
+        subs: &mut Subs,
+        var1: Variable,
+        var2: Variable,
+        mode: UnificationMode,
+    ) -> bool {
+        unifies(
+            &mut with_checkmate!({
+                on => Env::new(subs, None),
+                off => Env::new(subs),
+            }),
+            var1,
+            var2,
+            mode,
+        )
+    }
+
+    #[test]
+    fn unifies_leaves_subs_unchanged_on_success() {
+        let mut subs = Subs::new();
+
+        let record = record_of_str(&mut subs, "x");
+        let flex = new_var(&mut subs, FlexVar(None));
+        let len_before = subs.len();
+
+        assert!(unifies_vars(&mut subs, record, flex, UnificationMode::EQ));
+
+        assert_eq!(subs.len(), len_before);
+        assert!(!subs.equivalent_without_compacting(record, flex));
+        assert!(matches!(
+            subs.get_content_without_compacting(flex),
+            FlexVar(None)
+        ));
+    }
+
+    #[test]
+    fn unifies_leaves_subs_unchanged_on_failure() {
+        let mut subs = Subs::new();
+
+        let open = {
+            let ext = new_var(&mut subs, FlexVar(None));
+            let fields = RecordFields::insert_into_subs(
+                &mut subs,
+                [(Lowercase::from("x"), RecordField::Required(Variable::STR))],
+            );
+            new_var(&mut subs, Structure(FlatType::Record(fields, ext)))
+        };
+        let other = record_of_str(&mut subs, "y");
+        let len_before = subs.len();
+
+        assert!(!unifies_vars(&mut subs, open, other, UnificationMode::EQ));
+
+        assert_eq!(subs.len(), len_before);
+        assert!(!subs.equivalent_without_compacting(open, other));
+        match subs.get_content_without_compacting(open) {
+            Structure(FlatType::Record(_, ext)) => assert!(matches!(
+                subs.get_content_without_compacting(*ext),
+                FlexVar(None)
+            )),
+            other => panic!("expected a record, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn fresh_vars_are_deduplicated() {
+        let mut subs = Subs::new();
+
+        let mut open_record = |field: &str| {
+            let fields = RecordFields::insert_into_subs(
+                &mut subs,
+                [(Lowercase::from(field), RecordField::Required(Variable::STR))],
+            );
+            let ext = new_var(&mut subs, FlexVar(None));
+            new_var(&mut subs, Structure(FlatType::Record(fields, ext)))
+        };
+
+        let a = open_record("a");
+        let b = open_record("b");
+
+        let unified = unify_vars(&mut subs, a, b, UnificationMode::EQ);
+        let fresh_vars = unified.fresh_vars();
+
+        assert!(!fresh_vars.is_empty());
+        let unique: MutSet<_> = fresh_vars.iter().collect();
+        assert_eq!(unique.len(), fresh_vars.len());
+
+        let _ = unified.expect_success("{ a : Str }* ~ { b : Str }* should unify");
+    }
+
+    #[test]
+    fn present_grows_closed_tag_union() {
+        let mut subs = Subs::new();
+
+        let a = tag_union(&mut subs, &["A"]);
+        let a_b = tag_union(&mut subs, &["A", "B"]);
+
+        let _ = unify_vars(&mut subs, a, a_b, UnificationMode::PRESENT)
+            .expect_success("[A] += [A, B] should grow [A]");
+    }
+
+    #[test]
+    fn no_open_forbids_growing_closed_tag_union() {
+        let mut subs = Subs::new();
+
+        let a = tag_union(&mut subs, &["A"]);
+        let a_b = tag_union(&mut subs, &["A", "B"]);
+
+        assert!(matches!(
+            unify_vars(&mut subs, a, a_b, UnificationMode::NO_OPEN),
+            Unified::Failure(..)
+        ));
+    }
+
+    #[test]
+    fn no_open_allows_present_subset() {
+        let mut subs = Subs::new();
+
+        let a_b = tag_union(&mut subs, &["A", "B"]);
+        let a = tag_union(&mut subs, &["A"]);
+
+        let _ = unify_vars(&mut subs, a_b, a, UnificationMode::NO_OPEN)
+            .expect_success("[A, B] += [A] needs no new tags");
+    }
+
+    #[test]
+    fn demanded_vs_optional_field_names_the_field() {
+        let mut subs = Subs::new();
+
+        let demanded = {
+            let fields = RecordFields::insert_into_subs(
+                &mut subs,
+                [(Lowercase::from("x"), RecordField::Demanded(Variable::STR))],
+            );
+            new_var(
+                &mut subs,
+                Structure(FlatType::Record(fields, Variable::EMPTY_RECORD)),
+            )
+        };
+        let optional = {
+            let fields = RecordFields::insert_into_subs(
+                &mut subs,
+                [(Lowercase::from("x"), RecordField::Optional(Variable::STR))],
+            );
+            new_var(
+                &mut subs,
+                Structure(FlatType::Record(fields, Variable::EMPTY_RECORD)),
+            )
+        };
+
+        let outcome: Outcome<NoCollector> = unify_pool(
+            &mut with_checkmate!({
+                on => Env::new(&mut subs, None),
+                off => Env::new(&mut subs),
+            }),
+            &mut Vec::new(),
+            demanded,
+            optional,
+            UnificationMode::EQ,
+        );
+
+        assert!(outcome
+            .mismatches
+            .contains(&Mismatch::OptionalFieldConflict(Lowercase::from("x"))));
+    }
+
+    #[test]
+    fn deeply_nested_unions_unify() {
+        let mut subs = Subs::new();
+
+        let left = nested_union(&mut subs, 32);
+        let right = nested_union(&mut subs, 32);
+
+        let _ = unify_vars(&mut subs, left, right, UnificationMode::EQ)
+            .expect_success("identical nested unions should unify");
+
+        let mut var = left;
+        for _ in 0..32 {
+            match subs.get_content_without_compacting(var) {
+                Structure(FlatType::TagUnion(tags, _)) => {
+                    let (_, payload) = tags.iter_from_subs(&subs).next().unwrap();
+                    var = payload[0];
+                }
+                other => panic!("expected a non-recursive tag union, got {:?}", other),
+            }
+        }
+    }
+
+    /// `[A payload]`, along with `payload`, a flex var.
+    fn union_with_flex_payload(subs: &mut Subs) -> (Variable, Variable) {
+        let payload = new_var(subs, FlexVar(None));
+        let tags = UnionTags::insert_into_subs(subs, [(TagName("A".into()), [payload])]);
+        let union = new_var(
+            subs,
+            Structure(FlatType::TagUnion(
+                tags,
+                TagExt::Any(Variable::EMPTY_TAG_UNION),
+            )),
+        );
+
+        (union, payload)
+    }
+
+    #[test]
+    fn occurs_cache_hit_skips_the_check() {
+        let mut subs = Subs::new();
+        let (union, payload) = union_with_flex_payload(&mut subs);
+
+        let mut env = with_checkmate!({
+            on => Env::new(&mut subs, None),
+            off => Env::new(&mut subs),
+        });
+
+        assert!(env.occurs_cached(union).is_ok());
+
+        // Tie the knot, `union = [A union]`, behind the environment's back.
+        let desc = env.get(union);
+        Subs::union(&mut env, payload, union, desc);
+
+        assert!(env.occurs(union).is_err());
+        assert!(env.occurs_cached(union).is_ok());
+    }
+
+    #[test]
+    fn occurs_cache_is_invalidated_by_modifications() {
+        let tie_the_knot: [fn(&mut Env, Variable, Variable); 3] = [
+            |env, union, payload| {
+                let desc = env.get(union);
+                env.union(payload, union, desc);
+            },
+            |env, union, payload| {
+                let desc = env.get(union);
+                env.set(payload, desc);
+            },
+            |env, union, payload| {
+                let content = *env.get_content_without_compacting(union);
+                env.set_content(payload, content);
+            },
+        ];
+
+        for tie_the_knot in tie_the_knot {
+            let mut subs = Subs::new();
+            let (union, payload) = union_with_flex_payload(&mut subs);
+
+            let mut env = with_checkmate!({
+                on => Env::new(&mut subs, None),
+                off => Env::new(&mut subs),
+            });
+
+            assert!(env.occurs_cached(union).is_ok());
+            assert!(env.occurs_cached(payload).is_ok());
+
+            tie_the_knot(&mut env, union, payload);
+
+            assert!(env.occurs_cached(union).is_err());
+        }
+    }
+
+    #[test]
+    fn reports_whether_unification_changed_anything() {
+        let mut subs = Subs::new();
+
+        let record = record_of_str(&mut subs, "x");
+        let flex = new_var(&mut subs, FlexVar(None));
+
+        let changed = |unified: Unified| match unified {
+            Unified::Success { changed, .. } => changed,
+            Unified::Failure(..) => panic!("unification should succeed"),
+        };
+
+        assert!(changed(unify_vars(
+            &mut subs,
+            flex,
+            record,
+            UnificationMode::EQ
+        )));
+        assert!(!changed(unify_vars(
+            &mut subs,
+            flex,
+            record,
+            UnificationMode::EQ
+        )));
+    }
+
+    #[test]
+    fn apply_argument_mismatch_reports_position() {
+        let mut subs = Subs::new();
+
+        let dict_of = |subs: &mut Subs, key, value| {
+            let args = VariableSubsSlice::insert_into_subs(subs, [key, value]);
+            new_var(subs, Structure(FlatType::Apply(Symbol::DICT_DICT, args)))
+        };
+        let str_str = dict_of(&mut subs, Variable::STR, Variable::STR);
+        let str_i64 = dict_of(&mut subs, Variable::STR, Variable::I64);
+
+        let mut env = with_checkmate!({
+            on => Env::new(&mut subs, None),
+            off => Env::new(&mut subs),
+        });
+        let mut vars = Vec::new();
+        let outcome: Outcome<NoCollector> =
+            unify_pool(&mut env, &mut vars, str_str, str_i64, UnificationMode::EQ);
+
+        assert!(outcome.mismatches.contains(&Mismatch::NthArgument(1)));
+        assert!(!outcome.mismatches.contains(&Mismatch::NthArgument(0)));
+    }
+
+    #[test]
+    fn nested_apply_argument_mismatch_reports_outermost_position() {
+        let mut subs = Subs::new();
+
+        let list_of = |subs: &mut Subs, elem| {
+            let args = VariableSubsSlice::insert_into_subs(subs, [elem]);
+            new_var(subs, Structure(FlatType::Apply(Symbol::LIST_LIST, args)))
+        };
+        let dict_of = |subs: &mut Subs, key, value| {
+            let args = VariableSubsSlice::insert_into_subs(subs, [key, value]);
+            new_var(subs, Structure(FlatType::Apply(Symbol::DICT_DICT, args)))
+        };
+        let list_str = list_of(&mut subs, Variable::STR);
+        let list_i64 = list_of(&mut subs, Variable::I64);
+        let str_list_str = dict_of(&mut subs, Variable::STR, list_str);
+        let str_list_i64 = dict_of(&mut subs, Variable::STR, list_i64);
+
+        let mut env = with_checkmate!({
+            on => Env::new(&mut subs, None),
+            off => Env::new(&mut subs),
+        });
+        let mut vars = Vec::new();
+        let outcome: Outcome<NoCollector> = unify_pool(
+            &mut env,
+            &mut vars,
+            str_list_str,
+            str_list_i64,
+            UnificationMode::EQ,
+        );
+
+        let positions: Vec<_> = outcome
+            .mismatches
+            .iter()
+            .filter(|mismatch| matches!(mismatch, Mismatch::NthArgument(_)))
+            .collect();
+        assert_eq!(positions, [&Mismatch::NthArgument(1)]);
+    }
+
+    #[test]
+    fn func_argument_mismatch_reports_no_position() {
+        let mut subs = Subs::new();
+
+        let func_of = |subs: &mut Subs, arg| {
+            let args = VariableSubsSlice::insert_into_subs(subs, [arg]);
+            let closure = new_var(subs, FlexVar(None));
+            new_var(
+                subs,
+                Structure(FlatType::Func(args, closure, Variable::STR)),
+            )
+        };
+        let str_to_str = func_of(&mut subs, Variable::STR);
+        let i64_to_str = func_of(&mut subs, Variable::I64);
+
+        let mut env = with_checkmate!({
+            on => Env::new(&mut subs, None),
+            off => Env::new(&mut subs),
+        });
+        let mut vars = Vec::new();
+        let outcome: Outcome<NoCollector> = unify_pool(
+            &mut env,
+            &mut vars,
+            str_to_str,
+            i64_to_str,
+            UnificationMode::EQ,
+        );
+
+        assert!(!outcome.mismatches.is_empty());
+        assert!(!outcome
+            .mismatches
+            .iter()
+            .any(|mismatch| matches!(mismatch, Mismatch::NthArgument(_))));
+    }
+
+    #[test]
+    fn func_arity_mismatch_reports_both_arities() {
+        let mut subs = Subs::new();
+
+        let func_of = |subs: &mut Subs, args: &[Variable]| {
+            let args = VariableSubsSlice::insert_into_subs(subs, args.iter().copied());
+            let closure = new_var(subs, FlexVar(None));
+            new_var(
+                subs,
+                Structure(FlatType::Func(args, closure, Variable::STR)),
+            )
+        };
+        let two_args = func_of(&mut subs, &[Variable::STR, Variable::STR]);
+        let three_args = func_of(&mut subs, &[Variable::STR, Variable::STR, Variable::STR]);
+
+        let mut env = with_checkmate!({
+            on => Env::new(&mut subs, None),
+            off => Env::new(&mut subs),
+        });
+        let mut vars = Vec::new();
+        let outcome: Outcome<NoCollector> = unify_pool(
+            &mut env,
+            &mut vars,
+            three_args,
+            two_args,
+            UnificationMode::EQ,
+        );
+
+        assert!(outcome.mismatches.contains(&Mismatch::ArityMismatch {
+            expected: 2,
+            found: 3
+        }));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn merge_traces_ranks_when_asked() {
+        let mut subs = Subs::new();
+
+        let young = new_var(&mut subs, FlexVar(None));
+        subs.set_rank(young, Rank::toplevel().next());
+        let old = new_var(&mut subs, FlexVar(None));
+
+        RANK_TRACES.with(|traces| traces.replace(Some(Vec::new())));
+        let result = unify_vars(&mut subs, young, old, UnificationMode::EQ);
+        let traces = RANK_TRACES.with(|traces| traces.take()).unwrap();
+
+        assert!(matches!(result, Unified::Success { .. }));
+        assert_eq!(subs.get_rank(young), Rank::toplevel());
+
+        assert_eq!(traces.len(), 1, "{traces:?}");
+        assert!(traces[0].contains("rank 2 ~ 1 => 1"), "{}", traces[0]);
+    }
+
+    #[test]
+    fn unifier_undoes_failed_tries() {
+        let mut subs = Subs::new();
+
+        let var = new_var(&mut subs, FlexVar(None));
+        let x = record_of_str(&mut subs, "x");
+        let y = record_of_str(&mut subs, "y");
+
+        {
+            let mut unifier = Unifier::new(&mut subs);
+            assert!(unifier.try_unify(var, x, UnificationMode::EQ));
+            assert!(!unifier.try_unify(var, y, UnificationMode::EQ));
+            unifier.commit();
+        }
+
+        // The failed try did not touch `y`, and the committed one persists.
+        assert!(!subs.equivalent(var, y));
+        assert!(matches!(
+            subs.get_content_without_compacting(y),
+            Structure(_)
+        ));
+        assert!(subs.equivalent(var, x));
+    }
+
+    #[test]
+    fn unifier_returns_the_vars_of_committed_tries() {
+        let mut subs = Subs::new();
+
+        let mut open_record_of_str = |field: &str| {
+            let fields = RecordFields::insert_into_subs(
+                &mut subs,
+                [(Lowercase::from(field), RecordField::Required(Variable::STR))],
+            );
+            let ext = new_var(&mut subs, FlexVar(None));
+            new_var(&mut subs, Structure(FlatType::Record(fields, ext)))
+        };
+        let x = open_record_of_str("x");
+        let y = open_record_of_str("y");
+        let z = open_record_of_str("z");
+        let closed_x = record_of_str(&mut subs, "x");
+
+        let vars = {
+            let mut unifier = Unifier::new(&mut subs);
+            // merging the open records makes a fresh extension variable
+            assert!(unifier.try_unify(x, y, UnificationMode::EQ));
+            assert!(!unifier.try_unify(closed_x, z, UnificationMode::EQ));
+            unifier.commit()
+        };
+
+        assert!(!vars.is_empty());
+        assert!(vars.iter().all(|var| (var.index() as usize) < subs.len()));
+    }
+
+    #[test]
+    fn unifier_undoes_uncommitted_tries() {
+        let mut subs = Subs::new();
+
+        let var = new_var(&mut subs, FlexVar(None));
+        let x = record_of_str(&mut subs, "x");
+        let y = record_of_str(&mut subs, "y");
+
+        {
+            let mut unifier = Unifier::new(&mut subs);
+            assert!(unifier.try_unify(var, x, UnificationMode::EQ));
+            unifier.rollback();
+            assert!(unifier.try_unify(var, y, UnificationMode::EQ));
+        }
+
+        // Nothing was committed, so dropping the unifier undid everything.
+        assert!(!subs.equivalent(var, x));
+        assert!(!subs.equivalent(var, y));
+        assert!(matches!(
+            subs.get_content_without_compacting(var),
+            FlexVar(None)
+        ));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "sets both EQ and PRESENT")]
+    fn eq_and_present_mode_is_rejected() {
+        let mut subs = Subs::new();
+
+        let var1 = new_var(&mut subs, FlexVar(None));
+        let var2 = new_var(&mut subs, FlexVar(None));
+
+        let _ = unify_vars(
+            &mut subs,
+            var1,
+            var2,
+            UnificationMode::EQ | UnificationMode::PRESENT,
+        );
+    }
+
+    #[test]
+    fn collect_all_mode_reports_every_bad_field() {
+        let mut subs = Subs::new();
+
+        let mut record_of = |fields: [(&str, Variable); 3]| {
+            let fields = RecordFields::insert_into_subs(
+                &mut subs,
+                fields.map(|(name, var)| (Lowercase::from(name), RecordField::Required(var))),
+            );
+            new_var(
+                &mut subs,
+                Structure(FlatType::Record(fields, Variable::EMPTY_RECORD)),
+            )
+        };
+        let actual = record_of([
+            ("x", Variable::STR),
+            ("y", Variable::STR),
+            ("z", Variable::STR),
+        ]);
+        let expected = record_of([
+            ("x", Variable::I64),
+            ("y", Variable::STR),
+            ("z", Variable::U8),
+        ]);
+
+        let mut env = with_checkmate!({
+            on => Env::new(&mut subs, None),
+            off => Env::new(&mut subs),
+        });
+        let mut vars = Vec::new();
+        let mode = UnificationMode::EQ | UnificationMode::COLLECT_ALL;
+        let outcome: Outcome<NoCollector> = unify_pool(&mut env, &mut vars, actual, expected, mode);
+
+        assert!(outcome.mismatches.contains(&Mismatch::InField("x".into())));
+        assert!(outcome.mismatches.contains(&Mismatch::InField("z".into())));
+        assert!(!outcome.mismatches.contains(&Mismatch::InField("y".into())));
+    }
+
+    #[test]
+    fn collect_all_mode_drops_the_pool_vars_of_rolled_back_fields() {
+        let mut subs = Subs::new();
+        let ext1 = new_var(&mut subs, FlexVar(None));
+        let ext2 = new_var(&mut subs, FlexVar(None));
+
+        let mut record_of = |fields: &[(&str, Variable)], ext| {
+            let fields = fields
+                .iter()
+                .map(|(name, var)| (Lowercase::from(*name), RecordField::Required(*var)));
+            let fields = RecordFields::insert_into_subs(&mut subs, fields);
+            new_var(&mut subs, Structure(FlatType::Record(fields, ext)))
+        };
+
+        // unifying the inner records makes fresh variables before finding that n does not match
+        let inner1 = record_of(&[("a", Variable::STR), ("n", Variable::STR)], ext1);
+        let inner2 = record_of(&[("b", Variable::STR), ("n", Variable::I64)], ext2);
+        let actual = record_of(&[("x", inner1)], Variable::EMPTY_RECORD);
+        let expected = record_of(&[("x", inner2)], Variable::EMPTY_RECORD);
+
+        let mut env = with_checkmate!({
+            on => Env::new(&mut subs, None),
+            off => Env::new(&mut subs),
+        });
+        let mut vars = Vec::new();
+        let mode = UnificationMode::EQ | UnificationMode::COLLECT_ALL;
+        let outcome: Outcome<NoCollector> = unify_pool(&mut env, &mut vars, actual, expected, mode);
+
+        assert!(outcome.mismatches.contains(&Mismatch::InField("x".into())));
+        assert!(vars.iter().all(|var| (var.index() as usize) < subs.len()));
+    }
+
+    #[test]
+    fn closed_record_mode_rejects_extra_fields() {
+        let mut subs = Subs::new();
+
+        let open_x = {
+            let fields = RecordFields::insert_into_subs(
+                &mut subs,
+                [(Lowercase::from("x"), RecordField::Required(Variable::STR))],
+            );
+            let ext = new_var(&mut subs, FlexVar(None));
+            new_var(&mut subs, Structure(FlatType::Record(fields, ext)))
+        };
+        let x_and_y = {
+            let fields = RecordFields::insert_into_subs(
+                &mut subs,
+                [
+                    (Lowercase::from("x"), RecordField::Required(Variable::STR)),
+                    (Lowercase::from("y"), RecordField::Required(Variable::STR)),
+                ],
+            );
+            new_var(
+                &mut subs,
+                Structure(FlatType::Record(fields, Variable::EMPTY_RECORD)),
+            )
+        };
+
+        let closed = UnificationMode::EQ | UnificationMode::CLOSED_RECORD;
+        assert!(!unifies_vars(&mut subs, open_x, x_and_y, closed));
+        assert!(unifies_vars(
+            &mut subs,
+            open_x,
+            x_and_y,
+            UnificationMode::EQ
+        ));
+
+        // only extra fields on the right are forbidden
+        assert!(unifies_vars(&mut subs, x_and_y, open_x, closed));
+    }
+
+    #[test]
+    fn depth_limit_fails_cleanly() {
+        let mut subs = Subs::new();
+
+        let left = nested_union(&mut subs, 32);
+        let right = nested_union(&mut subs, 32);
+
+        let mut unify_with_limit = |limit| -> Outcome<NoCollector> {
+            let mut env = with_checkmate!({
+                on => Env::new(&mut subs, None),
+                off => Env::new(&mut subs),
+            });
+            env.set_depth_limit(limit);
+
+            let snapshot = env.snapshot();
+            let outcome = unify_pool(&mut env, &mut Vec::new(), left, right, UnificationMode::EQ);
+            env.rollback_to(snapshot);
+
+            outcome
+        };
+
+        let outcome = unify_with_limit(8);
+        assert!(outcome.mismatches.contains(&Mismatch::DepthExceeded));
+
+        let outcome = unify_with_limit(1_000);
+        assert!(outcome.mismatches.is_empty());
+    }
+
+    #[test]
+    fn tag_as_function_joins_existing_lambda_set() {
+        check_tag_as_function_joins_existing_lambda_set(true);
+        check_tag_as_function_joins_existing_lambda_set(false);
+    }
+
+    fn check_tag_as_function_joins_existing_lambda_set(tag_on_left: bool) {
+        let mut subs = Subs::new();
+
+        let mut lambdas = [Symbol::LIST_MAP, Symbol::LIST_WALK, Symbol::LIST_KEEP_IF];
+        lambdas.sort();
+        let (tag_lambda, closures) = (lambdas[0], &lambdas[1..]);
+
+        // f : Str -[[closure1, closure2]]-> ret
+        let func = new_var(&mut subs, FlexVar(None));
+        let solved = UnionLambdas::insert_into_subs(&mut subs, closures.iter().map(|&c| (c, [])));
+        let lambda_set = new_var(
+            &mut subs,
+            LambdaSet(self::LambdaSet {
+                solved,
+                recursion_var: OptVariable::NONE,
+                unspecialized: SubsSlice::default(),
+                ambient_function: func,
+            }),
+        );
+        let ret = new_var(&mut subs, FlexVar(None));
+        let args = VariableSubsSlice::insert_into_subs(&mut subs, [Variable::STR]);
+        subs.set_content(func, Structure(FlatType::Func(args, lambda_set, ret)));
+
+        // A, used as a function
+        let tag_names = SubsSlice::extend_new(&mut subs.tag_names, [TagName("A".into())]);
+        let tag_lambdas = SubsSlice::extend_new(&mut subs.symbol_names, [tag_lambda]);
+        let tag = new_var(
+            &mut subs,
+            Structure(FlatType::FunctionOrTagUnion(
+                tag_names,
+                tag_lambdas,
+                TagExt::Any(Variable::EMPTY_TAG_UNION),
+            )),
+        );
+
+        let (left, right) = if tag_on_left {
+            (tag, func)
+        } else {
+            (func, tag)
+        };
+        let _ = unify_vars(&mut subs, left, right, UnificationMode::EQ)
+            .expect_success("A should unify with a function from Str");
+
+        match subs.get_content_without_compacting(lambda_set) {
+            LambdaSet(self::LambdaSet { solved, .. }) => {
+                let names = subs.get_subs_slice(solved.labels());
+                assert_eq!(names, lambdas);
+            }
+            other => panic!("expected a lambda set, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn records_mismatch_source_when_asked() {
+        let mut subs = Subs::new();
+
+        let rigid = {
+            let name = SubsIndex::push_new(&mut subs.field_names, Lowercase::from("a"));
+            new_var(&mut subs, RigidVar(name))
+        };
+        let record = record_of_str(&mut subs, "x");
+
+        let mut unify_with_mode = |mode| -> Outcome<NoCollector> {
+            unify_pool(
+                &mut with_checkmate!({
+                    on => Env::new(&mut subs, None),
+                    off => Env::new(&mut subs),
+                }),
+                &mut Vec::new(),
+                rigid,
+                record,
+                mode,
+            )
+        };
+
+        let outcome = unify_with_mode(UnificationMode::EQ);
+        assert_eq!(outcome.mismatches, [Mismatch::TypeMismatch]);
+
+        let outcome =
+            unify_with_mode(UnificationMode::EQ | UnificationMode::RECORD_MISMATCH_SOURCE);
+        assert_eq!(
+            outcome.mismatches,
+            [
+                Mismatch::TypeMismatch,
+                Mismatch::TypeMismatchSource("Rigid {:?} with {:?}")
+            ]
+        );
+    }
+
+    #[test]
+    fn records_nested_mismatch_source_once() {
+        let mut subs = Subs::new();
+
+        let rigid = {
+            let name = SubsIndex::push_new(&mut subs.field_names, Lowercase::from("a"));
+            new_var(&mut subs, RigidVar(name))
+        };
+
+        // { x : { y : Str } } ~ { x : { y : a } }
+        let inner_str = record_of_str(&mut subs, "y");
+        let left = record_of(&mut subs, "x", inner_str);
+        let inner_rigid = record_of(&mut subs, "y", rigid);
+        let right = record_of(&mut subs, "x", inner_rigid);
+
+        let outcome: Outcome<NoCollector> = unify_pool(
+            &mut with_checkmate!({
+                on => Env::new(&mut subs, None),
+                off => Env::new(&mut subs),
+            }),
+            &mut Vec::new(),
+            left,
+            right,
+            UnificationMode::EQ | UnificationMode::RECORD_MISMATCH_SOURCE,
+        );
+
+        let sources: Vec<_> = outcome
+            .mismatches
+            .iter()
+            .filter_map(|m| match m {
+                Mismatch::TypeMismatchSource(source) => Some(*source),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(sources, ["trying to unify {:?} with rigid var {:?}"]);
+    }
+
+    /// `[Cons Str <rec>, Nil] as <rec>`, with the recursion var named `rec_name`.
+    fn recursive_str_list(subs: &mut Subs, rec_name: &str) -> Variable {
+        recursive_list(subs, rec_name, Variable::STR)
+    }
+
+    /// `[Cons elem <rec>, Nil] as <rec>`, with the recursion var named `rec_name`.
+    fn recursive_list(subs: &mut Subs, rec_name: &str, elem: Variable) -> Variable {
+        let rec_name = SubsIndex::push_new(&mut subs.field_names, Lowercase::from(rec_name));
+        let rec = new_var(
+            subs,
+            RecursionVar {
+                structure: Variable::NULL,
+                opt_name: Some(rec_name),
+            },
+        );
+        let tags = UnionTags::insert_into_subs(
+            subs,
+            [
+                (TagName("Cons".into()), vec![elem, rec]),
+                (TagName("Nil".into()), vec![]),
+            ],
+        );
+        let list = new_var(
+            subs,
+            Structure(FlatType::RecursiveTagUnion(
+                rec,
+                tags,
+                TagExt::Any(Variable::EMPTY_TAG_UNION),
+            )),
+        );
+        subs.set_content(
+            rec,
+            RecursionVar {
+                structure: list,
+                opt_name: Some(rec_name),
+            },
+        );
+        list
+    }
+
+    #[test]
+    fn types_equal_compares_without_unifying() {
+        let mut subs = Subs::new();
+
+        let x1 = record_of_str(&mut subs, "x");
+        let x2 = record_of_str(&mut subs, "x");
+        let y = record_of_str(&mut subs, "y");
+        let list1 = recursive_str_list(&mut subs, "a");
+        let list2 = recursive_str_list(&mut subs, "b");
+        let nil = tag_union(&mut subs, &["Nil"]);
+        let len_before = subs.len();
+
+        assert!(types_equal(&mut subs, x1, x2));
+        assert!(!types_equal(&mut subs, x1, y));
+        assert!(types_equal(&mut subs, list1, list2));
+        assert!(!types_equal(&mut subs, list1, nil));
+
+        assert_eq!(subs.len(), len_before);
+        assert!(!subs.equivalent_without_compacting(x1, x2));
+        assert!(!subs.equivalent_without_compacting(list1, list2));
+    }
+
+    #[test]
+    fn preview_unify_shows_merged_record_without_unifying() {
+        let mut subs = Subs::new();
+
+        let mut open_record_of_str = |field: &str| {
+            let fields = RecordFields::insert_into_subs(
+                &mut subs,
+                [(Lowercase::from(field), RecordField::Required(Variable::STR))],
+            );
+            let ext = new_var(&mut subs, FlexVar(None));
+            new_var(&mut subs, Structure(FlatType::Record(fields, ext)))
+        };
+        let x = open_record_of_str("x");
+        let y = open_record_of_str("y");
+        let closed_y = record_of_str(&mut subs, "y");
+
+        let (fields, ext) = match preview_unify(&mut subs, x, y) {
+            Some(ErrorType::Record(fields, ext)) => (fields, ext),
+            other => panic!("expected a record, got {other:?}"),
+        };
+        let names: Vec<_> = fields.keys().cloned().collect();
+
+        assert_eq!(names, [Lowercase::from("x"), Lowercase::from("y")]);
+        // the extension is a variable that only existed during the preview
+        assert!(matches!(ext, TypeExt::FlexOpen(_)));
+        assert!(!subs.equivalent_without_compacting(x, y));
+        assert!(matches!(
+            subs.get_content_without_compacting(x),
+            Structure(FlatType::Record(fields, _)) if fields.len() == 1
+        ));
+
+        assert!(preview_unify(&mut subs, closed_y, x).is_none());
+    }
+
+    #[test]
+    fn unify_copies_leaves_originals_untouched() {
+        let mut subs = Subs::new();
+
+        let ext = new_var(&mut subs, FlexVar(None));
+        let open_x = {
+            let fields = RecordFields::insert_into_subs(
+                &mut subs,
+                [(Lowercase::from("x"), RecordField::Required(Variable::STR))],
+            );
+            new_var(&mut subs, Structure(FlatType::Record(fields, ext)))
+        };
+        let x_and_y = {
+            let fields = RecordFields::insert_into_subs(
+                &mut subs,
+                [
+                    (Lowercase::from("x"), RecordField::Required(Variable::STR)),
+                    (Lowercase::from("y"), RecordField::Required(Variable::STR)),
+                ],
+            );
+            new_var(
+                &mut subs,
+                Structure(FlatType::Record(fields, Variable::EMPTY_RECORD)),
+            )
+        };
+
+        let describe = |subs: &Subs| {
+            [open_x, x_and_y, ext].map(|var| format!("{:?}", subs.get_without_compacting(var)))
+        };
+        let before = describe(&subs);
+
+        let (copy1, copy2, unified) = unify_copies(&mut subs, open_x, x_and_y, UnificationMode::EQ);
+        let _ =
+            unified.expect_success("copies of { x : Str }a and { x : Str, y : Str } should unify");
+        assert!(subs.equivalent_without_compacting(copy1, copy2));
+
+        assert_eq!(describe(&subs), before);
+        assert!(!subs.equivalent_without_compacting(open_x, x_and_y));
+        assert!(!subs.equivalent_without_compacting(copy1, open_x));
+    }
+
+    #[test]
+    fn differently_named_recursion_vars_unify_issue_2810() {
+        let mut subs = Subs::new();
+
+        let list1 = recursive_str_list(&mut subs, "a");
+        let list2 = recursive_str_list(&mut subs, "b");
+
+        let _ = unify_vars(&mut subs, list1, list2, UnificationMode::EQ)
+            .expect_success("equivalent recursive lists should unify");
+
+        assert!(subs.equivalent_without_compacting(list1, list2));
+        match *subs.get_content_without_compacting(list1) {
+            Structure(FlatType::RecursiveTagUnion(rec, tags, _)) => {
+                assert!(is_recursion_var(&subs, rec));
+
+                let (_, cons_payload) = tags.iter_from_subs(&subs).next().unwrap();
+                assert!(subs.equivalent_without_compacting(cons_payload[1], rec));
+            }
+            ref other => panic!("expected a recursive tag union, got {:?}", other),
+        }
+        assert!(subs.occurs(list1).is_ok());
+    }
+
+    fn recursion_var_of(subs: &Subs, list: Variable) -> Variable {
+        match *subs.get_content_without_compacting(list) {
+            Structure(FlatType::RecursiveTagUnion(rec, _, _)) => rec,
+            ref other => panic!("expected a recursive tag union, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn unifying_recursion_vars_reconciles_their_structures() {
+        let mut subs = Subs::new();
+
+        let list1 = recursive_str_list(&mut subs, "a");
+        let list2 = recursive_str_list(&mut subs, "b");
+        let rec1 = recursion_var_of(&subs, list1);
+        let rec2 = recursion_var_of(&subs, list2);
+
+        let _ = unify_vars(&mut subs, rec1, rec2, UnificationMode::EQ)
+            .expect_success("recursion vars of equal lists should unify");
+
+        assert!(subs.equivalent_without_compacting(rec1, rec2));
+        assert!(subs.equivalent_without_compacting(list1, list2));
+    }
+
+    #[test]
+    fn unifying_recursion_vars_of_different_structures_fails() {
+        let mut subs = Subs::new();
+
+        let strs = recursive_str_list(&mut subs, "a");
+        let ints = recursive_list(&mut subs, "b", Variable::I64);
+        let rec1 = recursion_var_of(&subs, strs);
+        let rec2 = recursion_var_of(&subs, ints);
+
+        let result = unify_vars(&mut subs, rec1, rec2, UnificationMode::EQ);
+
+        assert!(matches!(result, Unified::Failure(..)));
+        assert!(!subs.equivalent_without_compacting(strs, ints));
+    }
+}
@@ -534,6 +534,19 @@ impl<'a> RocDocAllocator<'a> {
             .append(line)
     }
 
+    /// Regions count columns in bytes; this is the column at which byte `column` of source line
+    /// `line` is displayed, so that underlines stay aligned after non-ASCII characters.
+    fn display_column(&self, line: u32, column: u32) -> u32 {
+        match self
+            .src_lines
+            .get(line as usize)
+            .and_then(|src| src.get(..column as usize))
+        {
+            Some(prefix) => prefix.chars().count() as u32,
+            None => column,
+        }
+    }
+
     pub fn region_all_the_things(
         &'a self,
         region: LineColumnRegion,
@@ -603,28 +616,24 @@ impl<'a> RocDocAllocator<'a> {
         }
 
         if error_highlight_line {
-            let overlapping = sub_region2.start().column < sub_region1.end().column;
+            let line = region.start().line;
+            let start1 = self.display_column(line, sub_region1.start().column);
+            let end1 = self.display_column(line, sub_region1.end().column);
+            let start2 = self.display_column(line, sub_region2.start().column);
+            let end2 = self.display_column(line, sub_region2.end().column);
+
+            let overlapping = start2 < end1;
 
             let highlight = if overlapping {
-                self.text(
-                    ERROR_UNDERLINE
-                        .repeat((sub_region2.end().column - sub_region1.start().column) as usize),
-                )
+                self.text(ERROR_UNDERLINE.repeat((end2 - start1) as usize))
             } else {
-                let highlight1 = ERROR_UNDERLINE
-                    .repeat((sub_region1.end().column - sub_region1.start().column) as usize);
+                let highlight1 = ERROR_UNDERLINE.repeat((end1 - start1) as usize);
                 let highlight2 = if sub_region1 == sub_region2 {
                     "".repeat(0)
                 } else {
-                    ERROR_UNDERLINE
-                        .repeat((sub_region2.end().column - sub_region2.start().column) as usize)
+                    ERROR_UNDERLINE.repeat((end2 - start2) as usize)
                 };
-                let in_between = " ".repeat(
-                    (sub_region2
-                        .start()
-                        .column
-                        .saturating_sub(sub_region1.end().column)) as usize,
-                );
+                let in_between = " ".repeat(start2.saturating_sub(end1) as usize);
 
                 self.text(highlight1)
                     .append(self.text(in_between))
@@ -639,7 +648,7 @@ impl<'a> RocDocAllocator<'a> {
                 .append(if sub_region1.is_empty() && sub_region2.is_empty() {
                     self.nil()
                 } else {
-                    self.text(" ".repeat(sub_region1.start().column as usize))
+                    self.text(" ".repeat(start1 as usize))
                         .indent(indent)
                         .append(highlight)
                         .annotate(error_annotation)
@@ -736,8 +745,10 @@ impl<'a> RocDocAllocator<'a> {
         }
 
         if error_highlight_line {
-            let highlight_text = ERROR_UNDERLINE
-                .repeat((sub_region.end().column - sub_region.start().column) as usize);
+            let start_column =
+                self.display_column(sub_region.start().line, sub_region.start().column);
+            let end_column = self.display_column(sub_region.end().line, sub_region.end().column);
+            let highlight_text = ERROR_UNDERLINE.repeat((end_column - start_column) as usize);
 
             let highlight_line = self
                 .line()
@@ -747,7 +758,7 @@ impl<'a> RocDocAllocator<'a> {
                 .append(if highlight_text.is_empty() {
                     self.nil()
                 } else {
-                    self.text(" ".repeat(sub_region.start().column as usize))
+                    self.text(" ".repeat(start_column as usize))
                         .indent(indent)
                         .append(self.text(highlight_text).annotate(Annotation::Error))
                 });